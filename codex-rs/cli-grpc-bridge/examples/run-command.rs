@@ -0,0 +1,87 @@
+//! Starts a `CodexCliService` against a stub CLI (a tiny shell script that
+//! echoes its args and env) and runs one `run_command` RPC against it over a
+//! Unix domain socket, end to end. This is the easiest way to see how the
+//! client, server, and `RunCommandRequest`/`RunCommandResponse` contract fit
+//! together without a real `codex` binary on hand.
+//!
+//! Run with:
+//!
+//! ```sh
+//! cargo run -p codex-cli-grpc-bridge --example run-command
+//! ```
+
+#[cfg(unix)]
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    use clap::Parser;
+    use codex_cli_grpc_bridge::Args;
+    use codex_cli_grpc_bridge::CodexClient;
+    use tokio_util::sync::CancellationToken;
+
+    let dir = tempfile::tempdir()?;
+
+    let stub_cli_path = dir.path().join("stub-cli.sh");
+    std::fs::write(
+        &stub_cli_path,
+        "#!/bin/sh\necho \"args: $*\"\necho \"GREETING=$GREETING\"\n",
+    )?;
+    std::fs::set_permissions(&stub_cli_path, std::fs::Permissions::from_mode(0o755))?;
+
+    let socket_path = dir.path().join("codex.sock");
+
+    let args = Args::parse_from([
+        "codex-cli-grpc-bridge".to_string(),
+        "--socket-path".to_string(),
+        socket_path.to_string_lossy().into_owned(),
+        "--cli-path".to_string(),
+        stub_cli_path.to_string_lossy().into_owned(),
+    ]);
+
+    let shutdown = CancellationToken::new();
+    let force_shutdown = CancellationToken::new();
+    let server = tokio::spawn(codex_cli_grpc_bridge::run_server(
+        args,
+        shutdown.clone(),
+        force_shutdown.clone(),
+    ));
+
+    // The socket file may not exist yet; `connect_uds_pooled` dials lazily
+    // and retries the dial during the first RPC instead of failing at
+    // connect time.
+    let mut client = CodexClient::connect_uds_pooled(&socket_path, 20)?;
+
+    let response = client
+        .run_command(
+            vec!["hello".to_string(), "world".to_string()],
+            std::collections::HashMap::from([("GREETING".to_string(), "hi".to_string())]),
+            String::new(),
+            Vec::new(),
+        )
+        .await?;
+
+    let stdout = String::from_utf8_lossy(&response.stdout);
+    println!("exit_code: {}", response.exit_code);
+    println!("stdout: {stdout}");
+    assert_eq!(response.exit_code, 0, "stub CLI exited non-zero");
+    assert!(
+        stdout.contains("args: hello world"),
+        "expected stub CLI's args echo in stdout, got: {stdout:?}"
+    );
+    assert!(
+        stdout.contains("GREETING=hi"),
+        "expected stub CLI's env echo in stdout, got: {stdout:?}"
+    );
+
+    shutdown.cancel();
+    server.await??;
+
+    println!("ok: round trip through CodexCliService matched the stub CLI's output");
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("this example only runs on unix (it relies on a Unix domain socket and a shell script stub CLI)");
+}
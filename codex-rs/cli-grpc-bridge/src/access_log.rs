@@ -0,0 +1,75 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::unbounded_channel;
+
+/// One JSON-lines record written per `run_command`/`run_interactive_command`
+/// invocation when `--access-log` is set. Deliberately excludes `stdout`; the
+/// point is an audit trail of what ran, not a copy of its output.
+#[derive(serde::Serialize)]
+pub struct AccessLogEntry {
+    pub timestamp_millis: i64,
+    pub request_id: Option<String>,
+    pub args: Vec<String>,
+    pub cwd: String,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+    /// The environment actually applied to the child, with any variable
+    /// matching `--redact-env` replaced by `"***"`. Always present, unlike
+    /// `stdin`, since names alone (with redacted values) are useful for an
+    /// audit trail without risking secrets.
+    pub env: std::collections::HashMap<String, String>,
+    /// Populated only when the server was started with `--log-stdin`, since
+    /// stdin can carry secrets that operators don't want sitting in a log
+    /// file by default.
+    pub stdin: Option<String>,
+    /// Echoes the request's `tag`, when set, so entries can be correlated
+    /// with business context without parsing `args`.
+    pub tag: Option<String>,
+}
+
+/// Sends [`AccessLogEntry`] records to the background writer task started by
+/// [`spawn`]. Cloning is cheap; every RPC handler holds its own clone.
+#[derive(Clone)]
+pub struct AccessLogHandle {
+    tx: UnboundedSender<AccessLogEntry>,
+}
+
+impl AccessLogHandle {
+    /// Enqueues `entry` for writing. Never blocks the caller; if the writer
+    /// task has stopped (e.g. the file became unwritable), the entry is
+    /// silently dropped rather than failing the RPC that generated it.
+    pub fn log(&self, entry: AccessLogEntry) {
+        let _ = self.tx.send(entry);
+    }
+}
+
+/// Opens `path` for appending and starts a background task that serializes
+/// each logged entry as one JSON line, so `AccessLogHandle::log` never has to
+/// wait on file I/O from an RPC-handling task.
+pub async fn spawn(path: &Path) -> std::io::Result<AccessLogHandle> {
+    let path: PathBuf = path.to_path_buf();
+    let file = tokio::fs::OpenOptions::new().create(true).append(true).open(&path).await?;
+    let (tx, mut rx) = unbounded_channel::<AccessLogEntry>();
+    tokio::spawn(async move {
+        let mut writer = tokio::io::BufWriter::new(file);
+        while let Some(entry) = rx.recv().await {
+            let line = match serde_json::to_string(&entry) {
+                Ok(line) => line,
+                Err(err) => {
+                    tracing::warn!(error = %err, "failed to serialize access log entry");
+                    continue;
+                }
+            };
+            if let Err(err) = writer.write_all(line.as_bytes()).await {
+                tracing::warn!(error = %err, path = %path.display(), "failed to write access log entry");
+                continue;
+            }
+            let _ = writer.write_all(b"\n").await;
+            let _ = writer.flush().await;
+        }
+    });
+    Ok(AccessLogHandle { tx })
+}
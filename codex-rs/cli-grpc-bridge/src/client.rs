@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use hyper_util::rt::TokioIo;
+use tonic::Request;
+use tonic::Status;
+use tonic::transport::Channel;
+use tonic::transport::Endpoint;
+use tonic::transport::Uri;
+use tower::service_fn;
+
+use crate::proto::RunCommandRequest;
+use crate::proto::RunCommandResponse;
+use crate::proto::RunCommandsRequest;
+use crate::proto::codex_cli_client::CodexCliClient;
+
+/// Placeholder authority `connect_with_connector` requires; the connector
+/// below ignores it and always dials `socket_path` instead.
+const UDS_CONNECT_URI: &str = "http://codex-cli-grpc-bridge.invalid";
+
+/// Dials `socket_path`, retrying with exponential backoff if it doesn't
+/// exist yet or refuses connections, up to `max_retries` times. Used by
+/// [`CodexClient::connect_uds_pooled`] so a client started before (or kept
+/// alive across) a server restart doesn't have to fail and retry the whole
+/// RPC itself.
+async fn connect_uds_with_retry(
+    socket_path: &Path,
+    max_retries: u32,
+) -> std::io::Result<codex_uds::UnixStream> {
+    let mut attempt = 0;
+    loop {
+        match codex_uds::UnixStream::connect(socket_path).await {
+            Ok(stream) => return Ok(stream),
+            Err(err)
+                if attempt < max_retries
+                    && matches!(
+                        err.kind(),
+                        std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+                    ) =>
+            {
+                let delay = Duration::from_millis(100 << attempt.min(10));
+                attempt += 1;
+                tokio::time::sleep(delay).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// A typed client for the [`CodexCli`](crate::proto::codex_cli_server::CodexCli)
+/// service that hides the `Endpoint`/`service_fn` boilerplate otherwise
+/// needed to dial a Unix domain socket with `tonic`.
+#[derive(Clone)]
+pub struct CodexClient {
+    inner: CodexCliClient<Channel>,
+}
+
+impl CodexClient {
+    /// Connects to a `CodexCli` server listening on `socket_path`.
+    pub async fn connect_uds(socket_path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let socket_path = socket_path.as_ref().to_path_buf();
+        let channel = Endpoint::try_from(UDS_CONNECT_URI)?
+            .connect_with_connector(service_fn(move |_: Uri| {
+                let socket_path = socket_path.clone();
+                async move {
+                    codex_uds::UnixStream::connect(&socket_path)
+                        .await
+                        .map(TokioIo::new)
+                }
+            }))
+            .await?;
+        Ok(Self {
+            inner: CodexCliClient::new(channel),
+        })
+    }
+
+    /// Connects to a `CodexCli` server on `socket_path` without waiting for
+    /// the first dial to succeed, and transparently reconnects afterward
+    /// whenever the transport needs a fresh connection — including the
+    /// first request and any reconnect after the server restarts during a
+    /// blue/green deploy. Each dial retries with exponential backoff (the
+    /// same schedule `--bind-retries` uses server-side) up to `max_retries`
+    /// times before failing whichever RPC triggered it.
+    ///
+    /// Unlike [`CodexClient::connect_uds`], this never fails at connect
+    /// time: since the returned channel connects lazily, a socket that
+    /// doesn't exist yet only surfaces as an error on the first RPC call.
+    pub fn connect_uds_pooled(
+        socket_path: impl AsRef<Path>,
+        max_retries: u32,
+    ) -> anyhow::Result<Self> {
+        let socket_path = socket_path.as_ref().to_path_buf();
+        let channel = Endpoint::try_from(UDS_CONNECT_URI)?.connect_with_connector_lazy(service_fn(
+            move |_: Uri| {
+                let socket_path = socket_path.clone();
+                async move {
+                    connect_uds_with_retry(&socket_path, max_retries)
+                        .await
+                        .map(TokioIo::new)
+                }
+            },
+        ));
+        Ok(Self {
+            inner: CodexCliClient::new(channel),
+        })
+    }
+
+    /// Enables gzip compression on this client: requests are sent compressed
+    /// and responses are accepted compressed. Only takes effect against a
+    /// server started with `--enable-compression`; an uncompressed server
+    /// simply ignores the accept-encoding and responds uncompressed.
+    #[must_use]
+    pub fn with_compression(mut self) -> Self {
+        self.inner = self
+            .inner
+            .send_compressed(tonic::codec::CompressionEncoding::Gzip)
+            .accept_compressed(tonic::codec::CompressionEncoding::Gzip);
+        self
+    }
+
+    /// Runs `args` to completion and returns the decoded response.
+    pub async fn run_command(
+        &mut self,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+        cwd: String,
+        stdin: Vec<u8>,
+    ) -> Result<RunCommandResponse, Status> {
+        let response = self
+            .inner
+            .run_command(Request::new(RunCommandRequest {
+                args,
+                cwd,
+                env,
+                stdin,
+                ..Default::default()
+            }))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Runs several requests in one RPC, executed server-side with bounded
+    /// parallelism, returning one response per request that actually ran.
+    /// Saves round trips compared to issuing `run_command` in a loop.
+    pub async fn run_commands(
+        &mut self,
+        requests: Vec<RunCommandRequest>,
+        stop_on_first_failure: bool,
+    ) -> Result<Vec<RunCommandResponse>, Status> {
+        let response = self
+            .inner
+            .run_commands(Request::new(RunCommandsRequest {
+                requests,
+                stop_on_first_failure,
+            }))
+            .await?;
+        Ok(response.into_inner().responses)
+    }
+
+    /// Checks that the server is up and returns its version and clock
+    /// reading. Used by [`crate::run_healthcheck`] as a liveness probe, but
+    /// also useful on its own to confirm a socket path is reachable.
+    pub async fn ping(&mut self) -> Result<crate::proto::PingResponse, Status> {
+        let response = self
+            .inner
+            .ping(Request::new(crate::proto::PingRequest {}))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Kills the in-flight `run_command` call tagged with `request_id`, if
+    /// one is still running. Returns whether a match was found.
+    pub async fn cancel_command(&mut self, request_id: String) -> Result<bool, Status> {
+        let response = self
+            .inner
+            .cancel_command(Request::new(crate::proto::CancelCommandRequest { request_id }))
+            .await?;
+        Ok(response.into_inner().found)
+    }
+
+    /// Lists every in-flight `run_command`/`run_interactive_command`
+    /// invocation on the server. `redact_args` omits each command's
+    /// argv from the result, for callers that shouldn't display it.
+    pub async fn list_running(
+        &mut self,
+        redact_args: bool,
+    ) -> Result<Vec<crate::proto::RunningCommand>, Status> {
+        let response = self
+            .inner
+            .list_running(Request::new(crate::proto::ListRunningRequest { redact_args }))
+            .await?;
+        Ok(response.into_inner().commands)
+    }
+
+    /// Triggers the server's graceful shutdown. `token` must match the
+    /// server's `--shutdown-token`, when it was started with one.
+    pub async fn shutdown_server(&mut self, token: Option<String>) -> Result<(), Status> {
+        self.inner
+            .shutdown_server(Request::new(crate::proto::ShutdownServerRequest { token }))
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+/// Declarative counterpart to the subset of [`Args`](crate::Args) flags an
+/// operator would otherwise have to repeat on every launch. Loaded via
+/// `--config` and merged into `Args` with CLI flags taking precedence over
+/// whatever the file sets.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub socket_paths: Option<Vec<PathBuf>>,
+    pub cli_path: Option<PathBuf>,
+    pub concurrency_limit: Option<usize>,
+    pub env_allowlist: Option<Vec<String>>,
+}
+
+/// Reads and parses `path` as a [`ConfigFile`].
+pub fn load(path: &Path) -> anyhow::Result<ConfigFile> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read config file {}", path.display()))?;
+    toml::from_str(&contents).with_context(|| format!("failed to parse config file {}", path.display()))
+}
@@ -0,0 +1,73 @@
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+
+/// An accepted connection from the platform socket (a Unix domain socket, or
+/// a Windows named pipe), or the optional TCP listener, unified so all can
+/// feed the same `tonic` incoming stream.
+pub enum Connection {
+    Unix(codex_uds::UnixStream),
+    Tcp(tokio::net::TcpStream),
+    // A TCP connection that has completed a TLS handshake, used when
+    // `--tls-cert`/`--tls-key` are set. The Unix socket is never wrapped this
+    // way; it always stays plaintext.
+    Tls(Box<tokio_rustls::server::TlsStream<tokio::net::TcpStream>>),
+    #[cfg(windows)]
+    NamedPipe(tokio::net::windows::named_pipe::NamedPipeServer),
+}
+
+impl AsyncRead for Connection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Connection::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+            Connection::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            Connection::Tls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+            #[cfg(windows)]
+            Connection::NamedPipe(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Connection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Connection::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+            Connection::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            Connection::Tls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+            #[cfg(windows)]
+            Connection::NamedPipe(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Connection::Unix(stream) => Pin::new(stream).poll_flush(cx),
+            Connection::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            Connection::Tls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+            #[cfg(windows)]
+            Connection::NamedPipe(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Connection::Unix(stream) => Pin::new(stream).poll_shutdown(cx),
+            Connection::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            Connection::Tls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+            #[cfg(windows)]
+            Connection::NamedPipe(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
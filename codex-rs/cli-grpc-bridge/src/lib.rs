@@ -0,0 +1,1154 @@
+//! A small gRPC bridge that exposes a `codex` CLI invocation to remote
+//! callers over the [`CodexCli`](proto::codex_cli_server::CodexCli) service.
+
+mod access_log;
+mod client;
+mod config_file;
+mod metrics;
+mod process_group;
+mod reflection;
+mod service;
+mod tls;
+
+#[path = "proto/codex.cli_bridge.v1.rs"]
+pub mod proto;
+
+mod connection;
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use clap::Parser;
+use tokio_util::sync::CancellationToken;
+
+use connection::Connection;
+pub use client::CodexClient;
+pub use service::CodexCliService;
+
+/// Command-line arguments for the `codex-cli-grpc-bridge` binary.
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Args {
+    /// Path to a Unix domain socket (or, on Windows, named pipe) the server
+    /// listens on. Repeat the flag to bind several sockets to the same
+    /// `CodexCliService`, e.g. to bind a fresh socket for a new deploy while
+    /// an old instance drains requests on its existing one. Can also be set
+    /// via `--config`; at least one socket path must come from one source
+    /// or the other, unless the server is started via systemd socket
+    /// activation.
+    ///
+    /// On Linux, a path starting with `@` binds an abstract-namespace socket
+    /// instead (e.g. `@codex.sock`), which leaves no socket file on disk, so
+    /// `--socket-mode` and the usual on-shutdown cleanup don't apply to it.
+    #[arg(long = "socket-path", value_name = "PATH")]
+    pub socket_paths: Vec<PathBuf>,
+
+    /// Path to the `codex` CLI binary to invoke for each request. Defaults
+    /// to `codex` unless overridden by `--config`.
+    #[arg(long = "cli-path", value_name = "PATH")]
+    pub cli_path: Option<PathBuf>,
+
+    /// Path to a TOML config file that can set `socket_paths`, `cli_path`,
+    /// `concurrency_limit`, and `env_allowlist`. Merged into the parsed CLI
+    /// flags with CLI flags taking precedence when both set the same value.
+    #[arg(long = "config", value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Instead of starting the server, connect to the first `--socket-path`
+    /// (or `--config`-supplied socket path), issue a `Ping`, and exit 0 if
+    /// it responds or non-zero otherwise. Lets a container or supervisor use
+    /// this same binary as a liveness probe without a separate client tool.
+    #[arg(long = "healthcheck", default_value_t = false)]
+    pub healthcheck: bool,
+
+    /// Optional `host:port` to additionally listen on over TCP, for setups
+    /// where the client and server don't share a filesystem. The Unix socket
+    /// always stays active.
+    #[arg(long = "listen-addr", value_name = "HOST:PORT")]
+    pub listen_addr: Option<SocketAddr>,
+
+    /// Comma-separated list of environment variable names that callers are
+    /// allowed to set on the spawned CLI. When unset, all variables in the
+    /// request are passed through unfiltered.
+    #[arg(long = "env-allowlist", value_name = "NAME,NAME,...", value_delimiter = ',')]
+    pub env_allowlist: Option<Vec<String>>,
+
+    /// Reject requests that set a variable outside `--env-allowlist` instead
+    /// of silently dropping it.
+    #[arg(long = "reject-disallowed-env", default_value_t = false)]
+    pub reject_disallowed_env: bool,
+
+    /// Reject requests that set an environment variable with a malformed
+    /// name (empty, containing `=`, or containing a NUL byte) instead of
+    /// dropping it and logging a warning.
+    #[arg(long = "reject-malformed-env", default_value_t = false)]
+    pub reject_malformed_env: bool,
+
+    /// A `NAME=VALUE` environment variable to set on every spawned CLI
+    /// before a request's own `env` is layered on top, so a client can still
+    /// override it. Repeat the flag to set several. Useful for pinning
+    /// defaults (e.g. `TERM`, proxy settings) that most callers shouldn't
+    /// have to specify themselves.
+    #[arg(long = "default-env", value_name = "NAME=VALUE", value_parser = parse_env_pair)]
+    pub default_env: Vec<(String, String)>,
+
+    /// Maximum number of bytes to capture per stream in `RunCommand`
+    /// responses. Excess output is dropped and the corresponding
+    /// `*_truncated` flag is set. Unset means unbounded.
+    #[arg(long = "max-output-bytes", value_name = "BYTES")]
+    pub max_output_bytes: Option<usize>,
+
+    /// Octal file mode applied to `--socket-path` immediately after binding,
+    /// before the server starts accepting connections. Defaults to owner-only
+    /// access so other local users on a shared host can't connect.
+    #[arg(
+        long = "socket-mode",
+        value_name = "MODE",
+        default_value = "0600",
+        value_parser = parse_octal_mode
+    )]
+    pub socket_mode: u32,
+
+    /// How long to wait for in-flight RPCs to finish after `shutdown` fires
+    /// before forcing the server to stop, in milliseconds. Keeps Ctrl-C from
+    /// truncating a `RunCommand` call that was nearly done.
+    #[arg(long = "drain-timeout-ms", value_name = "MILLISECONDS", default_value_t = 10_000)]
+    pub drain_timeout_ms: u64,
+
+    /// Confine `cwd` on every request to this directory's subtree, rejecting
+    /// any request whose `cwd` falls outside it. Unset allows any absolute,
+    /// existing directory.
+    #[arg(long = "allowed-cwd-root", value_name = "PATH")]
+    pub allowed_cwd_root: Option<PathBuf>,
+
+    /// Maximum number of `RunCommand`/`StreamCommand`/`RunInteractiveCommand`
+    /// calls allowed to run concurrently. Unset means unlimited.
+    #[arg(long = "concurrency-limit", value_name = "COUNT")]
+    pub concurrency_limit: Option<usize>,
+
+    /// How long an RPC waits for a permit under `--concurrency-limit` before
+    /// failing with `Status::resource_exhausted` instead of queuing
+    /// indefinitely. Ignored when `--concurrency-limit` is unset.
+    #[arg(long = "acquire-timeout-ms", value_name = "MILLISECONDS")]
+    pub acquire_timeout_ms: Option<u64>,
+
+    /// Maximum number of RPCs allowed to wait on a `--concurrency-limit`
+    /// permit at once. Once reached, further calls are rejected immediately
+    /// with `Status::resource_exhausted` instead of joining the wait, so
+    /// waiters can't pile up without bound. Ignored when
+    /// `--concurrency-limit` is unset.
+    #[arg(long = "max-queue-depth", value_name = "COUNT")]
+    pub max_queue_depth: Option<usize>,
+
+    /// `host:port` to serve Prometheus-format RED metrics on. Unset starts no
+    /// extra listener.
+    #[arg(long = "metrics-addr", value_name = "HOST:PORT")]
+    pub metrics_addr: Option<SocketAddr>,
+
+    /// Reject `RunCommand`/`StreamCommand`/`RunInteractiveCommand` requests
+    /// whose `args` is empty, instead of spawning the resolved CLI with no
+    /// arguments.
+    #[arg(long = "require-args", default_value_t = false)]
+    pub require_args: bool,
+
+    /// Comma-separated list of subcommands (matched against `args[0]`) that
+    /// `RunCommand`/`RunInteractiveCommand` are allowed to run, e.g. to
+    /// expose only `codex exec` from a server that otherwise defaults to the
+    /// full `codex` CLI. Requests whose first argument isn't in this list
+    /// fail with `permission_denied`. Unset, or given an empty list, allows
+    /// any subcommand.
+    #[arg(long = "allowed-commands", value_name = "NAME,NAME,...", value_delimiter = ',')]
+    pub allowed_commands: Option<Vec<String>>,
+
+    /// Allow a request's `cli_path` field to override `--cli-path` for that
+    /// call. Off by default so a compromised or misbehaving client can't
+    /// point the server at an arbitrary binary.
+    #[arg(long = "allow-cli-override", default_value_t = false)]
+    pub allow_cli_override: bool,
+
+    /// Confine every `--socket-path` to this directory's subtree: the
+    /// path's parent directory must canonicalize to this root or a
+    /// descendant of it, which rejects `..` components and symlinks that
+    /// would otherwise let a socket land outside the intended directory.
+    /// Relevant when `--socket-path` is assembled by a less-trusted
+    /// orchestration layer rather than typed by a human. Unset allows any
+    /// socket path, matching prior behavior.
+    #[arg(long = "socket-root", value_name = "PATH")]
+    pub socket_root: Option<PathBuf>,
+
+    /// Number of times to retry binding a `--socket-path` after an
+    /// address-in-use error, with exponential backoff between attempts.
+    /// Smooths over a supervisor restarting this server faster than the
+    /// previous process released its socket. Other bind errors (e.g.
+    /// permission denied) are never retried.
+    #[arg(long = "bind-retries", value_name = "COUNT", default_value_t = 0)]
+    pub bind_retries: u32,
+
+    /// PEM-encoded server certificate for `--listen-addr`. Requires
+    /// `--tls-key`. The Unix socket is unaffected; it never speaks TLS.
+    #[arg(long = "tls-cert", value_name = "PATH", requires = "tls_key")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM-encoded private key matching `--tls-cert`.
+    #[arg(long = "tls-key", value_name = "PATH", requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+
+    /// PEM-encoded CA bundle used to verify client certificates on
+    /// `--listen-addr`, enabling mutual TLS. Requires `--tls-cert` and
+    /// `--tls-key`; without it, TLS on `--listen-addr` is server-auth only.
+    #[arg(long = "tls-client-ca", value_name = "PATH", requires = "tls_cert")]
+    pub tls_client_ca: Option<PathBuf>,
+
+    /// Enables gzip compression: responses are compressed when the connecting
+    /// client also accepts gzip, and gzip-compressed requests are accepted.
+    /// Shrinks bandwidth for large `RunCommand`/`StreamCommand` output at the
+    /// cost of extra CPU on both ends. Off by default since most callers are
+    /// local UDS clients for whom the CPU cost isn't worth paying.
+    #[arg(long = "enable-compression", default_value_t = false)]
+    pub enable_compression: bool,
+
+    /// Serves tonic's gRPC server reflection service alongside `CodexCli`,
+    /// letting tools like `grpcurl` introspect available RPCs and message
+    /// shapes without the `.proto` file on hand. Off by default to minimize
+    /// the server's surface area.
+    #[arg(long = "enable-reflection", default_value_t = false)]
+    pub enable_reflection: bool,
+
+    /// Maximum size, in bytes, of a single decoded request message.
+    /// `RunInteractiveCommand` additionally enforces this per stdin chunk,
+    /// since its request is itself a stream rather than one message.
+    /// Rejecting oversized messages here, before they're buffered in full,
+    /// caps how much memory a single caller can force the server to hold.
+    #[arg(long = "max-request-bytes", value_name = "BYTES", default_value_t = 4 * 1024 * 1024)]
+    pub max_request_bytes: usize,
+
+    /// Directory under which a request's `stdout_file_path` must fall.
+    /// Unset rejects every `stdout_file_path` request, since redirecting a
+    /// child's stdout straight to a server-side file is a write primitive
+    /// that must be opted into explicitly.
+    #[arg(long = "allowed-stdout-dir", value_name = "PATH")]
+    pub allowed_stdout_dir: Option<PathBuf>,
+
+    /// Directory under which a request's `stdin_file_path` must fall. Unset
+    /// rejects every `stdin_file_path` request, since reading a server-side
+    /// file into a child's stdin is a read primitive that must be opted
+    /// into explicitly.
+    #[arg(long = "allowed-stdin-dir", value_name = "PATH")]
+    pub allowed_stdin_dir: Option<PathBuf>,
+
+    /// Honor a request's `max_cpu_seconds`/`max_memory_bytes`/`max_open_files`
+    /// fields by applying them as `setrlimit` limits on the spawned child.
+    /// Off by default since it lets a client make the server's own kernel
+    /// resource-constrain processes it spawns.
+    #[arg(long = "allow-rlimits", default_value_t = false)]
+    pub allow_rlimits: bool,
+
+    /// Honor a request's `nice` field by applying it via `setpriority`
+    /// before the spawned child execs. Off by default since a negative
+    /// value (raising priority) needs elevated privileges and even a
+    /// positive one lets one client starve another's commands of CPU on a
+    /// shared host.
+    #[arg(long = "allow-nice", default_value_t = false)]
+    pub allow_nice: bool,
+
+    /// cgroup v2 directory to place every spawned `RunCommand` child into,
+    /// by writing its pid to `<path>/cgroup.procs` right after spawn. Lets
+    /// an operator fold this server's children into container- or
+    /// systemd-managed resource accounting and limits. Unset leaves children
+    /// in whatever cgroup this server process itself is in. Linux-only;
+    /// setting it on another platform fails every `RunCommand` call.
+    #[arg(long = "cgroup-parent", value_name = "PATH")]
+    pub cgroup_parent: Option<PathBuf>,
+
+    /// Working directory to run a command in when its request leaves `cwd`
+    /// empty, instead of inheriting this server process's own working
+    /// directory (which depends on how and where it was launched). A
+    /// request's own `cwd` still takes precedence when set.
+    #[arg(long = "default-cwd", value_name = "PATH")]
+    pub default_cwd: Option<PathBuf>,
+
+    /// Number of times `RunCommand` retries `Command::spawn` after a
+    /// transient `EAGAIN`/`ENOMEM` error (fork temporarily unavailable or
+    /// out of memory), with exponential backoff between attempts. Other
+    /// spawn errors, like a missing or non-executable binary, still fail
+    /// immediately. Defaults to no retries.
+    #[arg(long = "spawn-retries", value_name = "COUNT", default_value_t = 0)]
+    pub spawn_retries: u32,
+
+    /// Append a JSON-lines audit record to this file for every
+    /// `RunCommand`/`RunInteractiveCommand` invocation: timestamp, args,
+    /// cwd, exit code, duration, and request id. Stdout contents are never
+    /// included; stdin contents only when `--log-stdin` is also set.
+    #[arg(long = "access-log", value_name = "PATH")]
+    pub access_log: Option<PathBuf>,
+
+    /// Comma-separated list of name patterns (case-insensitive substring
+    /// match against the variable name, e.g. `TOKEN` matches `API_TOKEN`)
+    /// whose values are replaced with `***` in `--access-log` entries. The
+    /// child process still receives the real value; this only affects what
+    /// gets written to disk.
+    #[arg(long = "redact-env", value_name = "PATTERN,PATTERN,...", value_delimiter = ',')]
+    pub redact_env: Option<Vec<String>>,
+
+    /// Include a UTF-8 (lossy) decode of `stdin` in each `--access-log`
+    /// record. Off by default since stdin often carries secrets; only
+    /// meaningful combined with `--access-log`.
+    #[arg(long = "log-stdin", default_value_t = false, requires = "access_log")]
+    pub log_stdin: bool,
+
+    /// After this many `RunCommand` invocations, trigger `shutdown` so a
+    /// supervisor can restart the process. Unset means never self-restart.
+    /// In-flight requests still drain normally via `--drain-timeout-ms`.
+    #[arg(long = "max-requests", value_name = "COUNT")]
+    pub max_requests: Option<u64>,
+
+    /// After the server has been running this long, trigger `shutdown` the
+    /// same way `--max-requests` does. Unset means never self-restart on a
+    /// time basis.
+    #[arg(long = "max-lifetime-secs", value_name = "SECONDS")]
+    pub max_lifetime_secs: Option<u64>,
+
+    /// Trigger `shutdown` once no `RunCommand` invocation has been in
+    /// flight for this long. A request that starts or finishes resets the
+    /// idle timer. Meant for on-demand spawned bridges that should reclaim
+    /// their resources once a short-lived session is done with them. Unset
+    /// means never self-restart on idleness.
+    #[arg(long = "idle-timeout", value_name = "SECONDS")]
+    pub idle_timeout_secs: Option<u64>,
+
+    /// Polls for a file at this path every 200ms in a background task; as
+    /// soon as it exists, triggers `shutdown` the same way `--max-requests`
+    /// does. Meant for deployment tooling that can't send the process a
+    /// signal but can still touch a file on a shared volume. Unset disables
+    /// the poll entirely.
+    #[arg(long = "drain-file", value_name = "PATH")]
+    pub drain_file: Option<PathBuf>,
+
+    /// Sleeps a random duration, up to this many milliseconds, after binding
+    /// every `--socket-path` but before accepting connections on any of
+    /// them. Smooths a thundering herd when a fleet of these servers all
+    /// restart at once and would otherwise spawn the CLI against shared
+    /// downstream infra in the same instant. Only delays *accepting*
+    /// connections — the socket is already bound and visible to clients
+    /// (who may queue a connection attempt against it) while this sleeps.
+    /// Unset, or a value of 0, never sleeps, matching prior behavior.
+    #[arg(long = "startup-jitter", value_name = "MILLIS")]
+    pub startup_jitter_ms: Option<u64>,
+
+    /// Honor a request's `extra_fds` field by creating a pipe per listed fd
+    /// number and handing the child the write end before it execs. Off by
+    /// default since it lets a client ask the server to open extra, unasked
+    /// for file descriptors in a process it spawns.
+    #[arg(long = "allow-extra-fds", default_value_t = false)]
+    pub allow_extra_fds: bool,
+
+    /// Restricts connections on every `--socket-path` to peers whose
+    /// effective uid is in this list, checked via `SO_PEERCRED` right after
+    /// accept and rejected before any RPC is dispatched. When combined with
+    /// `--allowed-gid`, a peer is authorized if it matches either list.
+    /// Linux-only; setting it or `--allowed-gid` on another platform is
+    /// rejected at startup. Unset imposes no uid restriction, matching
+    /// prior behavior.
+    #[arg(long = "allowed-uid", value_name = "UID,UID,...", value_delimiter = ',')]
+    pub allowed_uids: Option<Vec<u32>>,
+
+    /// Restricts connections the same way `--allowed-uid` does, but matches
+    /// the peer's effective gid instead. Useful when the socket is
+    /// group-accessible but only some members of that group should be able
+    /// to run commands.
+    #[arg(long = "allowed-gid", value_name = "GID,GID,...", value_delimiter = ',')]
+    pub allowed_gids: Option<Vec<u32>>,
+
+    /// Caps how many `RunCommand`/`RunInteractiveCommand` calls may run
+    /// concurrently for a single request's `client_id` (requests that leave
+    /// it unset all share one bucket), on top of whatever `--concurrency-limit`
+    /// already bounds server-wide. Unset imposes no per-client limit,
+    /// matching prior behavior.
+    #[arg(long = "per-client-limit", value_name = "N")]
+    pub per_client_limit: Option<usize>,
+
+    /// Required token a `ShutdownServer` RPC's `token` field must match
+    /// exactly, so an arbitrary client on the socket can't stop the server.
+    /// Unset lets any caller shut it down; set this on any socket reachable
+    /// by more than trusted management tooling.
+    #[arg(long = "shutdown-token", value_name = "TOKEN")]
+    pub shutdown_token: Option<String>,
+
+    /// Honor a request's `cpu_affinity` field by pinning the spawned child
+    /// to those CPU core ids via `sched_setaffinity` before it execs.
+    /// Off by default since it lets a client influence scheduling for other
+    /// processes sharing the host. Linux-only; a request setting
+    /// `cpu_affinity` against a server on another platform is rejected
+    /// regardless of this flag.
+    #[arg(long = "allow-cpu-affinity", default_value_t = false)]
+    pub allow_cpu_affinity: bool,
+
+    /// Maximum number of bytes allowed in a request's `stdin`, rejected with
+    /// `invalid_argument` before the command is spawned. More precise than
+    /// `--max-request-bytes` alone when stdin is the dominant part of an
+    /// oversized message. Unset means unbounded.
+    #[arg(long = "max-stdin-bytes", value_name = "BYTES")]
+    pub max_stdin_bytes: Option<usize>,
+
+    /// Strips `HTTP_PROXY`/`HTTPS_PROXY`/`ALL_PROXY` (and their lowercase
+    /// forms) from the spawned CLI's environment, applied after `env` and
+    /// `inherit_server_env`/`clear_env` are resolved. Prevents a command
+    /// meant to run against the local machine from picking up a proxy this
+    /// server process itself was started with (or a caller passed through).
+    #[arg(long = "strip-proxy-env", default_value_t = false)]
+    pub strip_proxy_env: bool,
+
+    /// When a command is killed for exceeding `timeout_ms` (or the gRPC
+    /// deadline), send `SIGTERM` first and wait up to this many milliseconds
+    /// for it to exit on its own before following up with `SIGKILL`, giving
+    /// well-behaved CLIs a chance to flush logs and clean up temp files.
+    /// Unset sends `SIGKILL` immediately, the prior behavior. Unix-only;
+    /// ignored elsewhere, since `SIGTERM` has no equivalent to escalate from.
+    #[arg(long = "term-grace-ms", value_name = "MILLISECONDS")]
+    pub term_grace_ms: Option<u64>,
+
+    /// Caps the rate of new `run_command` spawns, in commands per second,
+    /// to protect shared downstream services from bursty clients. Unset
+    /// means no limit, the prior behavior. Bursts up to this many commands
+    /// are allowed in a single second before the limit kicks in.
+    #[arg(long = "spawn-rate", value_name = "PER_SECOND")]
+    pub spawn_rate: Option<f64>,
+
+    /// When `--spawn-rate` is exhausted, reject the call immediately with
+    /// `resource_exhausted` instead of waiting for a token to refill.
+    #[arg(long = "spawn-rate-reject", default_value_t = false)]
+    pub spawn_rate_reject: bool,
+
+    /// Maximum time to wait for a `--spawn-rate` token before giving up
+    /// with `resource_exhausted`. Unset waits as long as it takes. Ignored
+    /// when `--spawn-rate-reject` is set.
+    #[arg(long = "spawn-rate-wait-ms", value_name = "MILLISECONDS")]
+    pub spawn_rate_wait_ms: Option<u64>,
+
+    /// Allows a request's `umask` to be honored, applying it via a
+    /// `umask()` `pre_exec` hook before the child execs. Unset rejects any
+    /// request that sets `umask` with `permission_denied`.
+    #[arg(long = "allow-umask", default_value_t = false)]
+    pub allow_umask: bool,
+
+    /// Resolves `--cli-path` (or its default) and checks that it's an
+    /// executable file once at startup, refusing to start if it isn't,
+    /// instead of only surfacing the failure on the first `run_command`.
+    #[arg(long = "verify-cli-on-startup", default_value_t = false)]
+    pub verify_cli_on_startup: bool,
+}
+
+impl Args {
+    /// Merges `--config`, when set, into the fields it can supply, with
+    /// whatever was already set by CLI flags taking precedence. Call this
+    /// once, right after [`Args::parse`] and before anything reads
+    /// `socket_paths`/`cli_path`/`concurrency_limit`/`env_allowlist`.
+    pub fn apply_config_file(&mut self) -> anyhow::Result<()> {
+        let Some(config_path) = &self.config else {
+            return Ok(());
+        };
+        let config = config_file::load(config_path)?;
+        if self.socket_paths.is_empty() {
+            if let Some(socket_paths) = config.socket_paths {
+                self.socket_paths = socket_paths;
+            }
+        }
+        if self.cli_path.is_none() {
+            self.cli_path = config.cli_path;
+        }
+        if self.concurrency_limit.is_none() {
+            self.concurrency_limit = config.concurrency_limit;
+        }
+        if self.env_allowlist.is_none() {
+            self.env_allowlist = config.env_allowlist;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a `clap` value as an octal file mode, e.g. `"0600"` or `"600"`.
+fn parse_octal_mode(value: &str) -> Result<u32, String> {
+    u32::from_str_radix(value.trim_start_matches("0o"), 8)
+        .map_err(|err| format!("invalid octal mode {value:?}: {err}"))
+}
+
+/// Parses a `clap` value as a `NAME=VALUE` pair, e.g. for `--default-env`.
+fn parse_env_pair(value: &str) -> Result<(String, String), String> {
+    let (name, value) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got {value:?}"))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Derives a Windows named pipe path from `--socket-path`, e.g.
+/// `/tmp/codex.sock` becomes `\\.\pipe\codex.sock`. Named pipes don't live on
+/// the filesystem the way Unix sockets do, so only the file name is kept.
+#[cfg(windows)]
+fn named_pipe_path(socket_path: &std::path::Path) -> String {
+    let name = socket_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "codex-cli-grpc-bridge".to_string());
+    format!(r"\\.\pipe\{name}")
+}
+
+/// Removes its socket path from the filesystem when dropped, so a Unix
+/// socket doesn't linger after the accept loop that owned it exits (e.g. on
+/// shutdown), which would otherwise make a later bind to the same path fail
+/// with `AddrInUse`. Only meaningful for a path-based socket; an
+/// abstract-namespace socket (see [`is_abstract_socket_path`]) has no
+/// filesystem entry to clean up, so callers skip constructing one for those.
+///
+/// Records the `(dev, ino)` of the socket file as it existed right after
+/// this process bound it, and only unlinks the path on drop if it still
+/// points at that same inode. Without this check, a slow-to-exit old
+/// instance could delete the socket file a newer instance just bound to the
+/// same path during an overlapping restart, breaking the new instance for
+/// every client that connects afterward.
+#[cfg(unix)]
+struct SocketCleanup {
+    path: PathBuf,
+    dev: u64,
+    ino: u64,
+}
+
+#[cfg(unix)]
+impl SocketCleanup {
+    fn new(path: PathBuf) -> std::io::Result<Self> {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = std::fs::metadata(&path)?;
+        Ok(Self {
+            path,
+            dev: metadata.dev(),
+            ino: metadata.ino(),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for SocketCleanup {
+    fn drop(&mut self) {
+        use std::os::unix::fs::MetadataExt;
+        let still_ours = std::fs::metadata(&self.path)
+            .map(|metadata| metadata.dev() == self.dev && metadata.ino() == self.ino)
+            .unwrap_or(false);
+        if still_ours {
+            let _ = std::fs::remove_file(&self.path);
+        } else {
+            tracing::warn!(
+                socket_path = %self.path.display(),
+                "not removing socket path on shutdown; it no longer points at the socket this process bound"
+            );
+        }
+    }
+}
+
+/// Whether `socket_path` uses the `@name` convention for a Linux
+/// abstract-namespace socket, which `codex_uds::UnixListener::bind` also
+/// recognizes. Such a socket has no filesystem entry, so callers must skip
+/// `chmod`-ing it and building a [`SocketCleanup`] for it.
+#[cfg(unix)]
+fn is_abstract_socket_path(socket_path: &std::path::Path) -> bool {
+    socket_path
+        .as_os_str()
+        .to_str()
+        .is_some_and(|path| path.starts_with('@'))
+}
+
+/// Maximum byte length of a Unix domain socket path, from `sockaddr_un`'s
+/// `sun_path` field size. Differs by platform; abstract-namespace paths
+/// (`@name`, Linux-only) share the same buffer.
+#[cfg(target_os = "linux")]
+const SUN_PATH_MAX: usize = 108;
+#[cfg(all(unix, not(target_os = "linux")))]
+const SUN_PATH_MAX: usize = 104;
+
+/// Rejects `socket_path` if it's too long for `sockaddr_un.sun_path`, which
+/// otherwise makes `bind` fail with a confusing `EINVAL`/`ENAMETOOLONG`
+/// instead of naming the actual limit and the path's actual length. `bind`
+/// also needs room for a trailing NUL, so the usable length is one less
+/// than `SUN_PATH_MAX`. For an abstract-namespace path, the leading `@`
+/// becomes a NUL byte rather than a path byte, so it doesn't count toward
+/// the limit itself but still needs to fit in the same buffer.
+#[cfg(unix)]
+fn validate_socket_path_length(socket_path: &std::path::Path) -> anyhow::Result<()> {
+    let path_str = socket_path.as_os_str().to_str().unwrap_or_default();
+    let len = if is_abstract_socket_path(socket_path) {
+        path_str.len() - 1
+    } else {
+        path_str.len()
+    };
+    let usable_max = SUN_PATH_MAX - 1;
+    if len > usable_max {
+        anyhow::bail!(
+            "socket path {socket_path:?} is {len} bytes, which exceeds this platform's \
+             {usable_max} byte Unix domain socket path limit"
+        );
+    }
+    Ok(())
+}
+
+/// Rejects `socket_path` unless its parent directory canonicalizes to
+/// `root` or a descendant of it. A no-op when `root` is `None`, which keeps
+/// behavior unchanged for servers that don't set `--socket-root`. Skips
+/// abstract-namespace sockets entirely, since they have no filesystem
+/// parent to confine.
+#[cfg(unix)]
+fn validate_socket_path(socket_path: &std::path::Path, root: Option<&std::path::Path>) -> anyhow::Result<()> {
+    let Some(root) = root else {
+        return Ok(());
+    };
+    if is_abstract_socket_path(socket_path) {
+        return Ok(());
+    }
+    let parent = socket_path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+    let parent = std::fs::canonicalize(parent)
+        .map_err(|err| anyhow::anyhow!("failed to canonicalize socket path {socket_path:?}: {err}"))?;
+    let root = std::fs::canonicalize(root)
+        .map_err(|err| anyhow::anyhow!("failed to canonicalize --socket-root {root:?}: {err}"))?;
+    if !parent.starts_with(&root) {
+        anyhow::bail!("socket path {socket_path:?} escapes --socket-root {root:?}");
+    }
+    Ok(())
+}
+
+/// Binds `socket_path`, retrying up to `max_retries` times with exponential
+/// backoff if the bind fails with `AddrInUse`. A previous server occupying
+/// the same path during a fast supervisor-driven restart is expected to
+/// release it shortly, so retrying smooths over that race. Any other error
+/// (e.g. permission denied) is returned immediately without retrying.
+#[cfg(unix)]
+async fn bind_with_retry(
+    socket_path: &std::path::Path,
+    max_retries: u32,
+) -> std::io::Result<codex_uds::UnixListener> {
+    let mut attempt = 0;
+    loop {
+        match codex_uds::UnixListener::bind(socket_path).await {
+            Ok(listener) => return Ok(listener),
+            Err(err) if err.kind() == std::io::ErrorKind::AddrInUse && attempt < max_retries => {
+                let delay = std::time::Duration::from_millis(100 << attempt.min(10));
+                tracing::warn!(
+                    socket_path = %socket_path.display(),
+                    attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    "socket address in use; retrying bind"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Picks a random duration in `[0, max_millis)` for `--startup-jitter`, or
+/// `Duration::ZERO` when unset (or 0), so the non-jittered path sleeps for
+/// no time at all rather than taking a different code path.
+fn startup_jitter_delay(max_millis: Option<u64>) -> std::time::Duration {
+    use rand::Rng;
+    match max_millis {
+        Some(0) | None => std::time::Duration::ZERO,
+        Some(max_millis) => std::time::Duration::from_millis(rand::rng().random_range(0..max_millis)),
+    }
+}
+
+/// Reports whether this process was started with a socket handed to it by
+/// systemd socket activation (`LISTEN_PID`/`LISTEN_FDS`). `LISTEN_PID` must
+/// match our own pid, not just be present, since it's inherited by any
+/// child process started afterward even when it wasn't the one meant to
+/// adopt the fd.
+#[cfg(unix)]
+fn is_systemd_socket_activated() -> bool {
+    let listen_pid = std::env::var("LISTEN_PID").ok().and_then(|pid| pid.parse::<u32>().ok());
+    let listen_fds = std::env::var("LISTEN_FDS").ok().and_then(|fds| fds.parse::<u32>().ok());
+    listen_pid == Some(std::process::id()) && listen_fds.is_some_and(|fds| fds > 0)
+}
+
+#[cfg(not(unix))]
+fn is_systemd_socket_activated() -> bool {
+    false
+}
+
+/// Checked once at startup: `--allowed-uid`/`--allowed-gid` need `SO_PEERCRED`,
+/// which this crate only supports on Linux, so setting either on another
+/// platform is rejected immediately rather than silently accepting every
+/// peer.
+fn validate_allowed_peer_credentials(_args: &Args) -> anyhow::Result<()> {
+    #[cfg(not(target_os = "linux"))]
+    if _args.allowed_uids.is_some() || _args.allowed_gids.is_some() {
+        anyhow::bail!("--allowed-uid/--allowed-gid require SO_PEERCRED, which is only supported on Linux");
+    }
+    Ok(())
+}
+
+/// Reports whether `conn`'s peer, as reported by `SO_PEERCRED`, is allowed to
+/// use the bridge. A peer is authorized if it matches `allowed_uids` or
+/// `allowed_gids` (either list, when both are set); when neither is set,
+/// every peer is allowed, matching prior behavior. Non-`Unix` connections
+/// (TCP, TLS, named pipes) have no `SO_PEERCRED` equivalent and are always
+/// allowed here; those transports have their own, separate access controls
+/// (TLS client certs, `--listen-addr` itself being opt-in). Always allows on
+/// non-Linux platforms, where `validate_allowed_peer_credentials` has
+/// already rejected a nonempty `allowed_uids`/`allowed_gids` at startup.
+fn peer_credentials_allowed(conn: &Connection, allowed_uids: Option<&[u32]>, allowed_gids: Option<&[u32]>) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        if allowed_uids.is_none() && allowed_gids.is_none() {
+            return true;
+        }
+        let Connection::Unix(stream) = conn else {
+            return true;
+        };
+        let Ok(creds) = stream.peer_cred() else {
+            return false;
+        };
+        return allowed_uids.is_some_and(|uids| uids.contains(&creds.uid))
+            || allowed_gids.is_some_and(|gids| gids.contains(&creds.gid));
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (conn, allowed_uids, allowed_gids);
+        true
+    }
+}
+
+/// Connects to the first `args.socket_paths` entry and issues a `Ping`,
+/// returning `Ok(())` if the server answers and an error otherwise. Intended
+/// for `--healthcheck`, where the caller maps the result to a process exit
+/// code rather than starting a server in this process.
+pub async fn run_healthcheck(args: &Args) -> anyhow::Result<()> {
+    let socket_path = args
+        .socket_paths
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("--healthcheck requires at least one --socket-path"))?;
+    let mut client = CodexClient::connect_uds(socket_path).await?;
+    client.ping().await?;
+    Ok(())
+}
+
+/// Resolves the `--cli-path` default used for requests that don't set an
+/// allowed `cli_path` override, and reports which of the resolution rules
+/// supplied it. Checked in order: the explicit flag/config value, then the
+/// `CODEX_GRPC_CLI_BIN` environment variable, then a `codex` binary sitting
+/// next to this server's own executable, then finally the bare name
+/// `codex`, left for `Command::spawn` to resolve against `$PATH`.
+fn resolve_default_cli_path(flag: Option<PathBuf>) -> (PathBuf, crate::proto::CliPathSource) {
+    if let Some(cli_path) = flag {
+        return (cli_path, crate::proto::CliPathSource::Flag);
+    }
+    if let Ok(cli_path) = std::env::var("CODEX_GRPC_CLI_BIN") {
+        return (PathBuf::from(cli_path), crate::proto::CliPathSource::Env);
+    }
+    let sibling = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("codex")))
+        .filter(|candidate| candidate.is_file());
+    if let Some(cli_path) = sibling {
+        return (cli_path, crate::proto::CliPathSource::Sibling);
+    }
+    (PathBuf::from("codex"), crate::proto::CliPathSource::Default)
+}
+
+/// Binds every path in `args.socket_paths` and serves the `CodexCli` service
+/// on all of them until `shutdown` fires. `shutdown` starts a graceful drain
+/// (stop accepting new connections, let in-flight RPCs finish, for up to
+/// `--drain-timeout-ms`); `force_shutdown` skips straight past that wait, for
+/// a caller that wants to honor a second, more urgent signal.
+pub async fn run_server(
+    args: Args,
+    shutdown: CancellationToken,
+    force_shutdown: CancellationToken,
+) -> anyhow::Result<()> {
+    if args.socket_paths.is_empty() && !is_systemd_socket_activated() {
+        anyhow::bail!(
+            "at least one --socket-path is required, whether on the command line, in --config, \
+             or via systemd socket activation (LISTEN_FDS)"
+        );
+    }
+    validate_allowed_peer_credentials(&args)?;
+    let allowed_uids = args.allowed_uids.clone();
+    let allowed_gids = args.allowed_gids.clone();
+    let (cli_path, cli_path_source) = resolve_default_cli_path(args.cli_path.clone());
+
+    // Picked once per server start so every listener's first accept waits
+    // the same amount, rather than each socket rolling its own delay.
+    let startup_jitter = startup_jitter_delay(args.startup_jitter_ms);
+
+    let (tx, rx) = tokio::sync::mpsc::channel(16);
+
+    #[cfg(unix)]
+    if is_systemd_socket_activated() {
+        tracing::info!("codex-cli-grpc-bridge adopting a socket-activated listener from systemd (LISTEN_FDS)");
+        // systemd hands activated fds to the process starting at fd 3
+        // (`SD_LISTEN_FDS_START`); this crate only ever requests one socket
+        // from a unit file, so there's exactly one to adopt.
+        const SD_LISTEN_FDS_START: std::os::fd::RawFd = 3;
+        // SAFETY: `LISTEN_PID`/`LISTEN_FDS` (checked by
+        // `is_systemd_socket_activated`) are systemd's contract that fd
+        // `SD_LISTEN_FDS_START` is a valid, already-listening socket handed
+        // to this exact process; nothing else in this process has touched
+        // or closed it yet.
+        let mut listener = unsafe { codex_uds::UnixListener::from_raw_fd(SD_LISTEN_FDS_START) }?;
+        let uds_tx = tx.clone();
+        let allowed_uids = allowed_uids.clone();
+        let allowed_gids = allowed_gids.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(startup_jitter).await;
+            loop {
+                let conn = listener.accept().await.map(Connection::Unix);
+                if let Ok(conn) = &conn {
+                    if !peer_credentials_allowed(conn, allowed_uids.as_deref(), allowed_gids.as_deref()) {
+                        tracing::warn!("rejecting connection from a peer outside --allowed-uid/--allowed-gid");
+                        continue;
+                    }
+                }
+                if uds_tx.send(conn).await.is_err() {
+                    break;
+                }
+            }
+        });
+    } else {
+        for socket_path in &args.socket_paths {
+            validate_socket_path_length(socket_path)?;
+            validate_socket_path(socket_path, args.socket_root.as_deref())?;
+            let mut listener = bind_with_retry(socket_path, args.bind_retries).await?;
+            let cleanup = if is_abstract_socket_path(socket_path) {
+                None
+            } else {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(socket_path, std::fs::Permissions::from_mode(args.socket_mode))?;
+                Some(SocketCleanup::new(socket_path.clone())?)
+            };
+            tracing::info!(socket_path = %socket_path.display(), "codex-cli-grpc-bridge listening");
+            let uds_tx = tx.clone();
+            let allowed_uids = allowed_uids.clone();
+            let allowed_gids = allowed_gids.clone();
+            tokio::spawn(async move {
+                let _cleanup = cleanup;
+                tokio::time::sleep(startup_jitter).await;
+                loop {
+                    let conn = listener.accept().await.map(Connection::Unix);
+                    if let Ok(conn) = &conn {
+                        if !peer_credentials_allowed(conn, allowed_uids.as_deref(), allowed_gids.as_deref()) {
+                            tracing::warn!("rejecting connection from a peer outside --allowed-uid/--allowed-gid");
+                            continue;
+                        }
+                    }
+                    if uds_tx.send(conn).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    }
+
+    #[cfg(windows)]
+    for socket_path in &args.socket_paths {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        let pipe_name = named_pipe_path(socket_path);
+        let mut server = ServerOptions::new()
+            .first_pipe_instance(true)
+            .create(&pipe_name)?;
+        tracing::info!(pipe_name, "codex-cli-grpc-bridge listening on named pipe");
+        let pipe_tx = tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(startup_jitter).await;
+            loop {
+                let accepted = match server.connect().await {
+                    Ok(()) => Ok(std::mem::replace(
+                        &mut server,
+                        match ServerOptions::new().create(&pipe_name) {
+                            Ok(next) => next,
+                            Err(err) => {
+                                let _ = pipe_tx.send(Err(err)).await;
+                                break;
+                            }
+                        },
+                    )),
+                    Err(err) => Err(err),
+                };
+                let conn = accepted.map(Connection::NamedPipe);
+                if pipe_tx.send(conn).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let access_log_handle = match &args.access_log {
+        Some(path) => Some(access_log::spawn(path).await?),
+        None => None,
+    };
+
+    let service_metrics = std::sync::Arc::new(metrics::Metrics::default());
+    let service = CodexCliService::new(
+        cli_path,
+        args.env_allowlist.map(|names| names.into_iter().collect()),
+        args.reject_disallowed_env,
+        args.max_output_bytes,
+        args.allowed_cwd_root,
+        args.concurrency_limit,
+        args.acquire_timeout_ms.map(std::time::Duration::from_millis),
+        std::sync::Arc::clone(&service_metrics),
+        args.require_args,
+        args.allowed_commands.map(|names| names.into_iter().collect()),
+        args.allow_cli_override,
+        args.max_request_bytes,
+        args.allowed_stdout_dir,
+        args.allow_rlimits,
+        args.allow_nice,
+        access_log_handle,
+        args.log_stdin,
+        args.spawn_retries,
+        args.default_cwd,
+        args.redact_env.unwrap_or_default(),
+        args.allowed_stdin_dir,
+        args.max_queue_depth,
+        args.cgroup_parent,
+        cli_path_source,
+        args.allow_extra_fds,
+        args.per_client_limit,
+        shutdown.clone(),
+        args.shutdown_token,
+        args.allow_cpu_affinity,
+        args.max_stdin_bytes,
+        args.strip_proxy_env,
+        args.term_grace_ms.map(std::time::Duration::from_millis),
+        args.spawn_rate,
+        args.spawn_rate_reject,
+        args.spawn_rate_wait_ms.map(std::time::Duration::from_millis),
+        args.allow_umask,
+        args.reject_malformed_env,
+        args.default_env.into_iter().collect(),
+    );
+
+    if args.verify_cli_on_startup {
+        service.verify_cli_path()?;
+    }
+
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    health_reporter
+        .set_serving::<proto::codex_cli_server::CodexCliServer<CodexCliService>>()
+        .await;
+
+    if let (Some(concurrency_limit), Some(max_queue_depth)) =
+        (args.concurrency_limit, args.max_queue_depth)
+    {
+        let service_metrics = std::sync::Arc::clone(&service_metrics);
+        let health_reporter = health_reporter.clone();
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            let mut degraded = false;
+            while !shutdown.is_cancelled() {
+                let saturated = service_metrics.in_flight() as usize >= concurrency_limit
+                    && service_metrics.queue_depth() as usize >= max_queue_depth;
+                if saturated && !degraded {
+                    tracing::warn!(
+                        concurrency_limit,
+                        max_queue_depth,
+                        "concurrency limit and queue both saturated; reporting NOT_SERVING"
+                    );
+                    health_reporter
+                        .set_not_serving::<proto::codex_cli_server::CodexCliServer<CodexCliService>>()
+                        .await;
+                    degraded = true;
+                } else if !saturated && degraded {
+                    tracing::info!("capacity freed up; reporting SERVING again");
+                    health_reporter
+                        .set_serving::<proto::codex_cli_server::CodexCliServer<CodexCliService>>()
+                        .await;
+                    degraded = false;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            }
+        });
+    }
+
+    if let Some(max_requests) = args.max_requests {
+        let service_metrics = std::sync::Arc::clone(&service_metrics);
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            loop {
+                if service_metrics.total_requests() >= max_requests {
+                    tracing::info!(max_requests, "max-requests reached; triggering shutdown");
+                    shutdown.cancel();
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            }
+        });
+    }
+
+    if let Some(drain_file) = args.drain_file {
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            loop {
+                if drain_file.exists() {
+                    tracing::info!(drain_file = %drain_file.display(), "drain file appeared; triggering shutdown");
+                    shutdown.cancel();
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            }
+        });
+    }
+
+    if let Some(max_lifetime_secs) = args.max_lifetime_secs {
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(max_lifetime_secs)).await;
+            tracing::info!(max_lifetime_secs, "max-lifetime reached; triggering shutdown");
+            shutdown.cancel();
+        });
+    }
+
+    if let Some(idle_timeout_secs) = args.idle_timeout_secs {
+        let service_metrics = std::sync::Arc::clone(&service_metrics);
+        let shutdown = shutdown.clone();
+        let idle_timeout = std::time::Duration::from_secs(idle_timeout_secs);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                if service_metrics.in_flight() == 0 && service_metrics.idle_duration() >= idle_timeout {
+                    tracing::info!(idle_timeout_secs, "idle timeout reached; triggering shutdown");
+                    shutdown.cancel();
+                    break;
+                }
+            }
+        });
+    }
+
+    if let Some(metrics_addr) = args.metrics_addr {
+        let metrics_listener = tokio::net::TcpListener::bind(metrics_addr).await?;
+        tracing::info!(%metrics_addr, "codex-cli-grpc-bridge serving metrics");
+        tokio::spawn(metrics::serve(metrics_listener, service_metrics));
+    }
+
+    if let Some(listen_addr) = args.listen_addr {
+        let tcp_listener = tokio::net::TcpListener::bind(listen_addr).await?;
+        let tls_acceptor = match (&args.tls_cert, &args.tls_key) {
+            (Some(cert), Some(key)) => {
+                tracing::info!(
+                    %listen_addr,
+                    mutual_tls = args.tls_client_ca.is_some(),
+                    "codex-cli-grpc-bridge also listening on TCP with TLS"
+                );
+                Some(tls::build_acceptor(cert, key, args.tls_client_ca.as_deref())?)
+            }
+            _ => {
+                tracing::info!(%listen_addr, "codex-cli-grpc-bridge also listening on TCP");
+                None
+            }
+        };
+        tokio::spawn(async move {
+            loop {
+                let accepted = tcp_listener.accept().await;
+                let conn = match (accepted, &tls_acceptor) {
+                    (Ok((stream, _addr)), Some(tls_acceptor)) => tls_acceptor
+                        .accept(stream)
+                        .await
+                        .map(|stream| Connection::Tls(Box::new(stream))),
+                    (Ok((stream, _addr)), None) => Ok(Connection::Tcp(stream)),
+                    (Err(err), _) => Err(err),
+                };
+                if tx.send(conn).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let drain = shutdown.clone();
+    let mut codex_cli_server =
+        proto::codex_cli_server::CodexCliServer::new(service).max_decoding_message_size(args.max_request_bytes);
+    if args.enable_compression {
+        codex_cli_server = codex_cli_server
+            .accept_compressed(tonic::codec::CompressionEncoding::Gzip)
+            .send_compressed(tonic::codec::CompressionEncoding::Gzip);
+    }
+    let reflection_service = if args.enable_reflection {
+        tracing::info!("codex-cli-grpc-bridge serving gRPC reflection");
+        let file_descriptor_set = prost_types::FileDescriptorSet {
+            file: vec![reflection::file_descriptor_proto()],
+        };
+        let encoded_file_descriptor_set: &'static [u8] =
+            Box::leak(prost::Message::encode_to_vec(&file_descriptor_set).into_boxed_slice());
+        Some(
+            tonic_reflection::server::Builder::configure()
+                .register_encoded_file_descriptor_set(encoded_file_descriptor_set)
+                .build_v1()?,
+        )
+    } else {
+        None
+    };
+    let serve = tonic::transport::Server::builder()
+        .add_service(health_service)
+        .add_service(codex_cli_server)
+        .add_optional_service(reflection_service)
+        .serve_with_incoming_shutdown(tokio_stream::wrappers::ReceiverStream::new(rx), async move {
+            drain.cancelled().await;
+            health_reporter
+                .set_not_serving::<proto::codex_cli_server::CodexCliServer<CodexCliService>>()
+                .await;
+        });
+
+    let drain_timeout_ms = args.drain_timeout_ms;
+    tokio::select! {
+        result = serve => result?,
+        () = async move {
+            shutdown.cancelled().await;
+            tokio::select! {
+                () = tokio::time::sleep(std::time::Duration::from_millis(drain_timeout_ms)) => {
+                    tracing::warn!(
+                        drain_timeout_ms,
+                        "drain timeout elapsed; forcing shutdown with RPCs possibly still in flight"
+                    );
+                }
+                () = force_shutdown.cancelled() => {
+                    tracing::warn!("force-shutdown signalled; not waiting out the drain timeout");
+                }
+            }
+        } => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    /// Simulates two overlapping server instances racing to use the same
+    /// socket path during a restart: the old instance's `SocketCleanup`
+    /// must not delete the file once a newer instance has rebound the path
+    /// to a fresh inode.
+    #[test]
+    fn socket_cleanup_does_not_delete_a_rebound_socket() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let socket_path = dir.path().join("codex.sock");
+        std::fs::write(&socket_path, b"").expect("failed to create initial socket file");
+
+        let stale_cleanup = SocketCleanup::new(socket_path.clone()).expect("failed to stat socket file");
+
+        // A newer instance unlinks and rebinds the same path, producing a
+        // file with a different inode.
+        std::fs::remove_file(&socket_path).expect("failed to remove socket file");
+        std::fs::write(&socket_path, b"").expect("failed to create replacement socket file");
+
+        drop(stale_cleanup);
+        assert!(
+            socket_path.exists(),
+            "stale SocketCleanup deleted a socket file it didn't create"
+        );
+
+        let fresh_cleanup = SocketCleanup::new(socket_path.clone()).expect("failed to stat socket file");
+        drop(fresh_cleanup);
+        assert!(
+            !socket_path.exists(),
+            "SocketCleanup left behind the socket file it created"
+        );
+    }
+}
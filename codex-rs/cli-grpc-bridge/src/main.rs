@@ -0,0 +1,72 @@
+use clap::Parser;
+use codex_cli_grpc_bridge::Args;
+use codex_cli_grpc_bridge::run_healthcheck;
+use codex_cli_grpc_bridge::run_server;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let mut args = Args::parse();
+    args.apply_config_file()?;
+
+    if args.healthcheck {
+        return run_healthcheck(&args).await;
+    }
+
+    let shutdown = CancellationToken::new();
+    let force_shutdown = CancellationToken::new();
+    spawn_shutdown_signal_task(shutdown.clone(), force_shutdown.clone());
+
+    run_server(args, shutdown, force_shutdown).await
+}
+
+/// Cancels `shutdown` on the first `SIGTERM`/`SIGINT` (or, on non-Unix,
+/// Ctrl-C), starting a graceful drain, then cancels `force_shutdown` on a
+/// second one so an operator who really wants the process to stop *now*
+/// doesn't have to wait out `--drain-timeout-ms`.
+#[cfg(unix)]
+fn spawn_shutdown_signal_task(shutdown: CancellationToken, force_shutdown: CancellationToken) {
+    use tokio::signal::unix::SignalKind;
+    use tokio::signal::unix::signal;
+
+    tokio::spawn(async move {
+        let (mut sigterm, mut sigint) = match (signal(SignalKind::terminate()), signal(SignalKind::interrupt())) {
+            (Ok(sigterm), Ok(sigint)) => (sigterm, sigint),
+            (Err(err), _) | (_, Err(err)) => {
+                tracing::warn!(error = %err, "failed to install shutdown signal handlers");
+                return;
+            }
+        };
+
+        tokio::select! {
+            _ = sigterm.recv() => {}
+            _ = sigint.recv() => {}
+        }
+        tracing::info!("received shutdown signal; draining in-flight requests");
+        shutdown.cancel();
+
+        tokio::select! {
+            _ = sigterm.recv() => {}
+            _ = sigint.recv() => {}
+        }
+        tracing::warn!("received second shutdown signal; forcing immediate shutdown");
+        force_shutdown.cancel();
+    });
+}
+
+#[cfg(not(unix))]
+fn spawn_shutdown_signal_task(shutdown: CancellationToken, force_shutdown: CancellationToken) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        tracing::info!("received Ctrl-C; draining in-flight requests");
+        shutdown.cancel();
+
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        tracing::warn!("received second Ctrl-C; forcing immediate shutdown");
+        force_shutdown.cancel();
+    });
+}
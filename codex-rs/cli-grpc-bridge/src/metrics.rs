@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+
+/// Maximum distinct `tag` values tracked individually by
+/// [`Metrics::record_tag`]; see `run_command_by_tag`.
+const MAX_DISTINCT_TAGS: usize = 64;
+
+/// Escapes `value` for use inside a Prometheus text-exposition label value,
+/// per the format's spec: backslash, double-quote, and newline are the only
+/// characters that must be escaped. `tag` is the only label value in this
+/// module sourced from a client-controlled string (`status_code` and the
+/// other label keys above are internally generated), so without this a
+/// quote in `tag` would break the label-value string for every metric
+/// rendered after it, and a newline would inject extra lines into the
+/// `/metrics` response.
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// RED metrics for the `CodexCli` service, rendered in Prometheus text
+/// exposition format over `--metrics-addr`. Hand-rolled rather than pulling
+/// in a metrics crate, since this is the only consumer.
+pub struct Metrics {
+    run_command_total: AtomicU64,
+    run_command_failures: Mutex<HashMap<String, u64>>,
+    /// Invocation counts keyed by a request's `tag`, for the
+    /// `codex_cli_run_command_by_tag_total` counter. Bounded to
+    /// `MAX_DISTINCT_TAGS` distinct keys; additional tags are folded into
+    /// `"other"` so a misbehaving or adversarial client can't grow this map
+    /// without bound.
+    run_command_by_tag: Mutex<HashMap<String, u64>>,
+    in_flight: AtomicI64,
+    spawn_latency_ms_sum: AtomicU64,
+    spawn_latency_count: AtomicU64,
+    duration_ms_sum: AtomicU64,
+    duration_count: AtomicU64,
+    /// When a `run_command` last started or finished, for `--idle-timeout`
+    /// tracking. `Instant` has no `Default`, so `Metrics` implements it by
+    /// hand below.
+    last_activity: Mutex<Instant>,
+    /// Requests currently waiting on a concurrency-limit permit, for
+    /// `--max-queue-depth` enforcement and the matching gauge below.
+    queue_depth: AtomicI64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            run_command_total: AtomicU64::new(0),
+            run_command_failures: Mutex::new(HashMap::new()),
+            run_command_by_tag: Mutex::new(HashMap::new()),
+            in_flight: AtomicI64::new(0),
+            spawn_latency_ms_sum: AtomicU64::new(0),
+            spawn_latency_count: AtomicU64::new(0),
+            duration_ms_sum: AtomicU64::new(0),
+            duration_count: AtomicU64::new(0),
+            last_activity: Mutex::new(Instant::now()),
+            queue_depth: AtomicI64::new(0),
+        }
+    }
+}
+
+impl Metrics {
+    /// Total number of `run_command` invocations started so far, for
+    /// `--max-requests` self-restart tracking.
+    pub fn total_requests(&self) -> u64 {
+        self.run_command_total.load(Ordering::Relaxed)
+    }
+
+    /// Number of `run_command` invocations currently in flight, for
+    /// `--idle-timeout` tracking.
+    pub fn in_flight(&self) -> i64 {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Time elapsed since a `run_command` invocation last started or
+    /// finished, for `--idle-timeout` tracking.
+    pub fn idle_duration(&self) -> Duration {
+        self.last_activity
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .elapsed()
+    }
+
+    /// Requests currently waiting on a concurrency-limit permit, for
+    /// `--max-queue-depth` enforcement.
+    pub fn queue_depth(&self) -> i64 {
+        self.queue_depth.load(Ordering::Relaxed)
+    }
+
+    /// Call when a request starts waiting for a concurrency-limit permit.
+    pub fn record_queued(&self) {
+        self.queue_depth.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Call when a request stops waiting, whether it got a permit, timed
+    /// out, or was rejected.
+    pub fn record_dequeued(&self) {
+        self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn touch_activity(&self) {
+        *self.last_activity.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Instant::now();
+    }
+
+    /// Call when a `run_command` invocation begins, before spawning.
+    pub fn record_start(&self) {
+        self.run_command_total.fetch_add(1, Ordering::Relaxed);
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        self.touch_activity();
+    }
+
+    /// Call once a request's `tag` is known, to attribute it in
+    /// `codex_cli_run_command_by_tag_total`. No-op when `tag` is unset or
+    /// empty.
+    pub fn record_tag(&self, tag: Option<&str>) {
+        let Some(tag) = tag.filter(|tag| !tag.is_empty()) else {
+            return;
+        };
+        let mut by_tag = self.run_command_by_tag.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let key = if by_tag.contains_key(tag) || by_tag.len() < MAX_DISTINCT_TAGS {
+            tag.to_string()
+        } else {
+            "other".to_string()
+        };
+        *by_tag.entry(key).or_insert(0) += 1;
+    }
+
+    /// Call once spawn has returned, successfully or not, with the elapsed
+    /// time since `record_start`.
+    pub fn record_spawn_latency(&self, latency_ms: u64) {
+        self.spawn_latency_ms_sum.fetch_add(latency_ms, Ordering::Relaxed);
+        self.spawn_latency_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Call when a `run_command` invocation finishes, with the gRPC status
+    /// code name (e.g. `"OK"`, `"DeadlineExceeded"`) and the total duration
+    /// since `record_start`.
+    pub fn record_completion(&self, status_code: &str, duration_ms: u64) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+        self.touch_activity();
+        self.duration_ms_sum.fetch_add(duration_ms, Ordering::Relaxed);
+        self.duration_count.fetch_add(1, Ordering::Relaxed);
+        if status_code != "OK" {
+            let mut failures = self
+                .run_command_failures
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            *failures.entry(status_code.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# TYPE codex_cli_run_command_total counter\n");
+        out.push_str(&format!(
+            "codex_cli_run_command_total {}\n",
+            self.run_command_total.load(Ordering::Relaxed)
+        ));
+
+        out.push_str("# TYPE codex_cli_run_command_failures_total counter\n");
+        let failures = self
+            .run_command_failures
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (status_code, count) in failures.iter() {
+            out.push_str(&format!(
+                "codex_cli_run_command_failures_total{{status_code=\"{status_code}\"}} {count}\n"
+            ));
+        }
+        drop(failures);
+
+        out.push_str("# TYPE codex_cli_run_command_by_tag_total counter\n");
+        let by_tag = self.run_command_by_tag.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (tag, count) in by_tag.iter() {
+            let tag = escape_label_value(tag);
+            out.push_str(&format!("codex_cli_run_command_by_tag_total{{tag=\"{tag}\"}} {count}\n"));
+        }
+        drop(by_tag);
+
+        out.push_str("# TYPE codex_cli_run_command_in_flight gauge\n");
+        out.push_str(&format!(
+            "codex_cli_run_command_in_flight {}\n",
+            self.in_flight.load(Ordering::Relaxed)
+        ));
+
+        out.push_str("# TYPE codex_cli_queue_depth gauge\n");
+        out.push_str(&format!(
+            "codex_cli_queue_depth {}\n",
+            self.queue_depth.load(Ordering::Relaxed)
+        ));
+
+        out.push_str("# TYPE codex_cli_spawn_latency_ms histogram\n");
+        out.push_str(&format!(
+            "codex_cli_spawn_latency_ms_sum {}\n",
+            self.spawn_latency_ms_sum.load(Ordering::Relaxed)
+        ));
+        out.push_str(&format!(
+            "codex_cli_spawn_latency_ms_count {}\n",
+            self.spawn_latency_count.load(Ordering::Relaxed)
+        ));
+
+        out.push_str("# TYPE codex_cli_command_duration_ms histogram\n");
+        out.push_str(&format!(
+            "codex_cli_command_duration_ms_sum {}\n",
+            self.duration_ms_sum.load(Ordering::Relaxed)
+        ));
+        out.push_str(&format!(
+            "codex_cli_command_duration_ms_count {}\n",
+            self.duration_count.load(Ordering::Relaxed)
+        ));
+
+        out
+    }
+}
+
+/// Serves `metrics.render()` as `text/plain` to any connection on
+/// `listener`, until the process exits. Requests are not parsed beyond
+/// draining the bytes a client sends; every connection gets the same body.
+pub async fn serve(listener: TcpListener, metrics: std::sync::Arc<Metrics>) {
+    loop {
+        let (mut stream, _addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to accept metrics connection");
+                continue;
+            }
+        };
+        let metrics = std::sync::Arc::clone(&metrics);
+        tokio::spawn(async move {
+            let mut discard = [0u8; 1024];
+            let _ = stream.read(&mut discard).await;
+            let body = metrics.render();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+            let _ = stream.shutdown().await;
+        });
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    /// A `tag` containing a quote or newline must not corrupt the
+    /// exposition format: it should come back escaped on a single line.
+    #[test]
+    fn tag_with_quote_and_newline_renders_as_one_well_formed_line() {
+        let metrics = Metrics::default();
+        metrics.record_tag(Some("weird\"tag\\with\nnewline"));
+
+        let body = metrics.render();
+        let line = body
+            .lines()
+            .find(|line| line.starts_with("codex_cli_run_command_by_tag_total{"))
+            .expect("missing by_tag metric line");
+        assert_eq!(
+            line,
+            r#"codex_cli_run_command_by_tag_total{tag="weird\"tag\\with\nnewline"} 1"#
+        );
+        assert_eq!(body.lines().filter(|line| line.starts_with("codex_cli_run_command_by_tag_total{")).count(), 1);
+    }
+}
@@ -0,0 +1,361 @@
+use tokio::process::Command;
+
+/// Spawns `command` in its own process group on Unix so that killing it also
+/// reaches any grandchildren it forks. This is a no-op on other platforms.
+pub fn set_own_process_group(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        command.process_group(0);
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = command;
+    }
+}
+
+/// Adds `pid` to the cgroup v2 hierarchy rooted at `cgroup_parent`, for
+/// `--cgroup-parent` placement. Writing a pid to `cgroup.procs` moves that
+/// process into the cgroup for accounting and resource limits; children it
+/// later forks inherit the same cgroup, so this only needs to run once,
+/// right after spawn. Fails if `cgroup_parent` doesn't exist or isn't a
+/// cgroup v2 directory, same as the kernel would on a direct write.
+#[cfg(target_os = "linux")]
+pub fn add_to_cgroup(cgroup_parent: &std::path::Path, pid: u32) -> std::io::Result<()> {
+    std::fs::write(cgroup_parent.join("cgroup.procs"), pid.to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn add_to_cgroup(_cgroup_parent: &std::path::Path, _pid: u32) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "cgroup placement is only supported on Linux",
+    ))
+}
+
+/// Sends `signal` to the process group led by `pid`. On non-Unix platforms
+/// this is a no-op; callers should fall back to killing the direct child.
+#[cfg(unix)]
+pub fn kill_process_group(pid: u32, signal: libc::c_int) {
+    // Negative pid targets the whole process group rooted at `pid`, which is
+    // valid because `set_own_process_group` makes the child its own group
+    // leader (pgid == pid).
+    // SAFETY: `kill` has no preconditions beyond a valid signal number.
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), signal);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn kill_process_group(_pid: u32, _signal: i32) {}
+
+/// Per-request `setrlimit` limits, applied to a spawned child before
+/// `execvp` so the kernel enforces them for the lifetime of the process.
+/// Gated behind `--allow-rlimits`; see [`apply_rlimits`].
+#[derive(Clone, Copy, Default)]
+pub struct ResourceLimits {
+    pub max_cpu_seconds: Option<u64>,
+    pub max_memory_bytes: Option<u64>,
+    pub max_open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_cpu_seconds.is_none() && self.max_memory_bytes.is_none() && self.max_open_files.is_none()
+    }
+}
+
+/// Installs a `pre_exec` hook on `command` that applies `limits` via
+/// `setrlimit` in the forked child, before it execs the target binary. A
+/// limit that's exceeded afterwards kills the process with `SIGKILL` (CPU,
+/// memory) or makes further `open()` calls fail (open files); either way
+/// `terminating_signal`/`exit_code` on the response reflects the outcome
+/// normally, since this runs entirely inside the kernel.
+#[cfg(unix)]
+pub fn apply_rlimits(command: &mut Command, limits: ResourceLimits) {
+    use std::os::unix::process::CommandExt;
+
+    if limits.is_empty() {
+        return;
+    }
+
+    // SAFETY: the closure only calls `setrlimit`, which is async-signal-safe,
+    // and touches no shared state; it runs in the forked child between
+    // `fork` and `exec` as required by `pre_exec`.
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(max_cpu_seconds) = limits.max_cpu_seconds {
+                set_rlimit(libc::RLIMIT_CPU, max_cpu_seconds)?;
+            }
+            if let Some(max_memory_bytes) = limits.max_memory_bytes {
+                set_rlimit(libc::RLIMIT_AS, max_memory_bytes)?;
+            }
+            if let Some(max_open_files) = limits.max_open_files {
+                set_rlimit(libc::RLIMIT_NOFILE, max_open_files)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub fn apply_rlimits(_command: &mut Command, _limits: ResourceLimits) {}
+
+/// Installs a `pre_exec` hook on `command` that calls `setpriority` with
+/// `nice` in the forked child, before it execs the target binary, so the
+/// kernel scheduler deprioritizes (or, for a negative value, prioritizes)
+/// the whole invocation relative to other processes on the host. Gated
+/// behind `--allow-nice`; see [`super::service::CodexCliService`]'s
+/// `validate_nice`, which rejects values outside the standard -20..=19
+/// range before this is ever called.
+#[cfg(unix)]
+pub fn apply_nice(command: &mut Command, nice: i32) {
+    use std::os::unix::process::CommandExt;
+
+    // SAFETY: the closure only calls `setpriority`, which is async-signal-safe,
+    // and touches no shared state; it runs in the forked child between
+    // `fork` and `exec` as required by `pre_exec`.
+    unsafe {
+        command.pre_exec(move || {
+            // SAFETY: `PRIO_PROCESS` with a pid of 0 targets the calling
+            // (forked, not-yet-exec'd) process, and `nice` was already
+            // validated to fit `setpriority`'s expected range.
+            if libc::setpriority(libc::PRIO_PROCESS, 0, nice) == 0 {
+                Ok(())
+            } else {
+                Err(std::io::Error::last_os_error())
+            }
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub fn apply_nice(_command: &mut Command, _nice: i32) {}
+
+/// Installs a `pre_exec` hook on `command` that calls `umask` with `mask` in
+/// the forked child, before it execs the target binary, so files the
+/// command creates get the intended permissions regardless of the server
+/// process's own umask. Gated behind `--allow-umask`; see
+/// [`super::service::CodexCliService`]'s `validate_umask`, which rejects
+/// values outside the valid 0..=0o777 octal range before this is ever
+/// called.
+#[cfg(unix)]
+pub fn apply_umask(command: &mut Command, mask: u32) {
+    use std::os::unix::process::CommandExt;
+
+    // SAFETY: the closure only calls `umask`, which is async-signal-safe,
+    // and touches no shared state; it runs in the forked child between
+    // `fork` and `exec` as required by `pre_exec`.
+    unsafe {
+        command.pre_exec(move || {
+            // SAFETY: `umask` has no failure mode; `mask` was already
+            // validated to fit within the standard 0..=0o777 octal range.
+            libc::umask(mask);
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub fn apply_umask(_command: &mut Command, _mask: u32) {}
+
+/// Number of CPU cores this host reports online, via
+/// `sysconf(_SC_NPROCESSORS_ONLN)`. Used to validate a request's
+/// `cpu_affinity` core ids before [`apply_cpu_affinity`] is ever called.
+#[cfg(target_os = "linux")]
+pub fn available_cpu_count() -> usize {
+    // SAFETY: `sysconf` has no preconditions; `_SC_NPROCESSORS_ONLN` is a
+    // valid `name` value and the call returns -1 on error rather than
+    // touching memory it wasn't given.
+    let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    count.max(0) as usize
+}
+
+/// Installs a `pre_exec` hook on `command` that calls `sched_setaffinity`
+/// with `cores` in the forked child, before it execs the target binary, so
+/// the kernel scheduler only ever runs it on those cores. Gated behind
+/// `--allow-cpu-affinity`; see
+/// [`super::service::CodexCliService::validate_cpu_affinity`], which
+/// rejects core ids outside `available_cpu_count()` before this is ever
+/// called. Linux-only.
+#[cfg(target_os = "linux")]
+pub fn apply_cpu_affinity(command: &mut Command, cores: Vec<u32>) {
+    use std::os::unix::process::CommandExt;
+
+    if cores.is_empty() {
+        return;
+    }
+
+    // SAFETY: the closure only calls `CPU_ZERO`/`CPU_SET`/`sched_setaffinity`,
+    // all async-signal-safe, and touches no shared state; it runs in the
+    // forked child between `fork` and `exec` as required by `pre_exec`.
+    unsafe {
+        command.pre_exec(move || {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            libc::CPU_ZERO(&mut set);
+            for &core in &cores {
+                libc::CPU_SET(core as usize, &mut set);
+            }
+            if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0 {
+                Ok(())
+            } else {
+                Err(std::io::Error::last_os_error())
+            }
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn apply_cpu_affinity(_command: &mut Command, _cores: Vec<u32>) {}
+
+/// Creates one pipe per fd number in `fds` and installs a `pre_exec` hook
+/// that `dup2`s each pipe's write end onto its requested fd in the forked
+/// child before it execs, for `extra_fds`. Returns the read end of each
+/// pipe, in the same order as `fds`, and the write ends, which the caller
+/// must close in the parent (via [`close_extra_fd_write_ends`]) once
+/// `Command::spawn` returns.
+#[cfg(unix)]
+pub fn open_extra_fd_pipes(
+    command: &mut Command,
+    fds: &[u32],
+) -> std::io::Result<(Vec<(u32, std::fs::File)>, Vec<libc::c_int>)> {
+    use std::os::fd::FromRawFd;
+    use std::os::unix::process::CommandExt;
+
+    let mut reads = Vec::with_capacity(fds.len());
+    let mut write_fds = Vec::with_capacity(fds.len());
+    let mut dup_pairs = Vec::with_capacity(fds.len());
+    for &fd in fds {
+        let mut ends = [0i32; 2];
+        // SAFETY: `ends` is a valid, writable array of two ints for `pipe2`
+        // to fill in. `O_CLOEXEC` keeps both ends from leaking into the
+        // child across `exec` until `pre_exec` below explicitly `dup2`s the
+        // write end onto its target, which clears `FD_CLOEXEC` on the new
+        // descriptor as intended.
+        if unsafe { libc::pipe2(ends.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+            let err = std::io::Error::last_os_error();
+            close_extra_fd_write_ends(&write_fds);
+            return Err(err);
+        }
+        let [read_fd, write_fd] = ends;
+        // SAFETY: `read_fd` was just returned by `pipe2` above and is not
+        // owned anywhere else yet.
+        reads.push((fd, unsafe { std::fs::File::from_raw_fd(read_fd) }));
+        write_fds.push(write_fd);
+        dup_pairs.push((write_fd, fd as libc::c_int));
+    }
+
+    // SAFETY: the closure only calls `dup2`/`close`, both async-signal-safe,
+    // and touches no shared state; it runs in the forked child between
+    // `fork` and `exec` as required by `pre_exec`.
+    unsafe {
+        command.pre_exec(move || {
+            for &(write_fd, target_fd) in &dup_pairs {
+                if libc::dup2(write_fd, target_fd) < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            for &(write_fd, _) in &dup_pairs {
+                libc::close(write_fd);
+            }
+            Ok(())
+        });
+    }
+
+    Ok((reads, write_fds))
+}
+
+#[cfg(not(unix))]
+pub fn open_extra_fd_pipes(
+    _command: &mut Command,
+    _fds: &[u32],
+) -> std::io::Result<(Vec<(u32, std::fs::File)>, Vec<i32>)> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "extra fds are only supported on Unix",
+    ))
+}
+
+/// Closes the parent's copy of each `extra_fds` pipe write end returned by
+/// [`open_extra_fd_pipes`], once the child (if one was spawned) has `dup2`'d
+/// its own, independent copy onto the target fd during `fork`. A no-op on
+/// non-Unix platforms, where `open_extra_fd_pipes` never returns any.
+pub fn close_extra_fd_write_ends(write_fds: &[i32]) {
+    #[cfg(unix)]
+    {
+        for &write_fd in write_fds {
+            // SAFETY: `write_fd` was returned by `open_extra_fd_pipes` above
+            // and is closed here exactly once.
+            unsafe {
+                libc::close(write_fd);
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = write_fds;
+    }
+}
+
+#[cfg(unix)]
+fn set_rlimit(resource: libc::c_int, limit: u64) -> std::io::Result<()> {
+    let rlim = libc::rlimit {
+        rlim_cur: limit as libc::rlim_t,
+        rlim_max: limit as libc::rlim_t,
+    };
+    // SAFETY: `rlim` is a valid, fully-initialized `rlimit` and `resource` is
+    // one of the `RLIMIT_*` constants above.
+    if unsafe { libc::setrlimit(resource, &rlim) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// A snapshot of `getrusage(RUSAGE_CHILDREN, ...)`. `tokio::process` doesn't
+/// expose `wait4`, so there's no way to get rusage for exactly one child;
+/// instead, callers take one snapshot before spawning and another after
+/// `wait()` returns and diff the cumulative counters (see
+/// [`ResourceUsage::since`]). This is only accurate for `user_cpu_ms`/
+/// `system_cpu_ms` when no other child of this process is reaped in the same
+/// window; `max_rss_kb` is a high-water mark across every child this process
+/// has ever reaped, not necessarily this one's own peak.
+#[derive(Clone, Copy, Default)]
+pub struct ResourceUsage {
+    pub user_cpu_ms: u64,
+    pub system_cpu_ms: u64,
+    pub max_rss_kb: u64,
+}
+
+impl ResourceUsage {
+    /// Best-effort per-command usage: CPU time diffed as `self` (the later
+    /// snapshot) minus `earlier`, and `max_rss_kb` taken as-is from `self` since it's
+    /// already a running high-water mark rather than a resettable counter.
+    pub fn since(&self, earlier: ResourceUsage) -> ResourceUsage {
+        ResourceUsage {
+            user_cpu_ms: self.user_cpu_ms.saturating_sub(earlier.user_cpu_ms),
+            system_cpu_ms: self.system_cpu_ms.saturating_sub(earlier.system_cpu_ms),
+            max_rss_kb: self.max_rss_kb,
+        }
+    }
+}
+
+#[cfg(unix)]
+pub fn children_resource_usage() -> ResourceUsage {
+    // SAFETY: `usage` is fully populated by `getrusage` before being read.
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_CHILDREN, &mut usage) } != 0 {
+        return ResourceUsage::default();
+    }
+    ResourceUsage {
+        user_cpu_ms: (usage.ru_utime.tv_sec as u64) * 1000 + (usage.ru_utime.tv_usec as u64) / 1000,
+        system_cpu_ms: (usage.ru_stime.tv_sec as u64) * 1000 + (usage.ru_stime.tv_usec as u64) / 1000,
+        // Linux reports ru_maxrss in kilobytes already; other Unixes vary,
+        // but this crate only targets Linux/macOS deployments.
+        max_rss_kb: usage.ru_maxrss as u64,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn children_resource_usage() -> ResourceUsage {
+    ResourceUsage::default()
+}
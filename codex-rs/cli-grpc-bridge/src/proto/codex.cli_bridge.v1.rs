@@ -0,0 +1,1184 @@
+// This file is @generated by prost-build.
+#![allow(clippy::trivially_copy_pass_by_ref)]
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunCommandRequest {
+    #[prost(string, repeated, tag = "1")]
+    pub args: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "2")]
+    pub cwd: ::prost::alloc::string::String,
+    #[prost(uint64, optional, tag = "3")]
+    pub timeout_ms: ::core::option::Option<u64>,
+    #[prost(map = "string, string", tag = "4")]
+    pub env:
+        ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub stdin: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, optional, tag = "6")]
+    pub cli_path: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, tag = "7")]
+    pub merge_streams: bool,
+    #[prost(bool, tag = "8")]
+    pub dry_run: bool,
+    #[prost(bool, tag = "9")]
+    pub decode_utf8: bool,
+    #[prost(string, optional, tag = "10")]
+    pub stdout_file_path: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "11")]
+    pub max_cpu_seconds: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "12")]
+    pub max_memory_bytes: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "13")]
+    pub max_open_files: ::core::option::Option<u64>,
+    #[prost(bool, tag = "14")]
+    pub inherit_server_env: bool,
+    #[prost(string, optional, tag = "15")]
+    pub request_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(int32, optional, tag = "16")]
+    pub nice: ::core::option::Option<i32>,
+    #[prost(string, optional, tag = "17")]
+    pub stdin_file_path: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, tag = "18")]
+    pub clear_env: bool,
+    #[prost(uint32, repeated, tag = "19")]
+    pub extra_fds: ::prost::alloc::vec::Vec<u32>,
+    #[prost(bool, tag = "20")]
+    pub encode_base64: bool,
+    #[prost(string, optional, tag = "21")]
+    pub client_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint32, repeated, tag = "22")]
+    pub cpu_affinity: ::prost::alloc::vec::Vec<u32>,
+    #[prost(uint64, optional, tag = "23")]
+    pub expected_output_bytes: ::core::option::Option<u64>,
+    #[prost(uint32, optional, tag = "24")]
+    pub umask: ::core::option::Option<u32>,
+    #[prost(string, optional, tag = "25")]
+    pub tag: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(int32, repeated, tag = "26")]
+    pub retry_on_exit_codes: ::prost::alloc::vec::Vec<i32>,
+    #[prost(uint32, optional, tag = "27")]
+    pub max_retries: ::core::option::Option<u32>,
+    #[prost(uint64, optional, tag = "28")]
+    pub tail_bytes: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InteractiveCommandInput {
+    #[prost(oneof = "interactive_command_input::Input", tags = "1, 2, 3")]
+    pub input: ::core::option::Option<interactive_command_input::Input>,
+}
+/// Nested message and enum types in `InteractiveCommandInput`.
+pub mod interactive_command_input {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Input {
+        #[prost(message, tag = "1")]
+        Start(super::RunCommandRequest),
+        #[prost(bytes, tag = "2")]
+        StdinChunk(::prost::alloc::vec::Vec<u8>),
+        #[prost(bool, tag = "3")]
+        CloseStdin(bool),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunCommandResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub stdout: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub stderr: ::prost::alloc::vec::Vec<u8>,
+    #[prost(int32, tag = "3")]
+    pub exit_code: i32,
+    #[prost(bool, tag = "4")]
+    pub stdout_truncated: bool,
+    #[prost(bool, tag = "5")]
+    pub stderr_truncated: bool,
+    #[prost(uint32, tag = "6")]
+    pub pid: u32,
+    #[prost(int32, optional, tag = "7")]
+    pub terminating_signal: ::core::option::Option<i32>,
+    #[prost(bytes = "vec", tag = "8")]
+    pub merged_output: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bool, tag = "9")]
+    pub merged_output_truncated: bool,
+    #[prost(message, optional, tag = "10")]
+    pub dry_run: ::core::option::Option<DryRunPlan>,
+    #[prost(string, optional, tag = "11")]
+    pub stdout_utf8: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "12")]
+    pub stderr_utf8: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "13")]
+    pub merged_output_utf8: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "14")]
+    pub stdout_bytes_written: ::core::option::Option<u64>,
+    #[prost(int64, optional, tag = "15")]
+    pub started_at_millis: ::core::option::Option<i64>,
+    #[prost(int64, optional, tag = "16")]
+    pub finished_at_millis: ::core::option::Option<i64>,
+    #[prost(uint64, optional, tag = "17")]
+    pub user_cpu_ms: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "18")]
+    pub system_cpu_ms: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "19")]
+    pub max_rss_kb: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "20")]
+    pub queue_wait_ms: ::core::option::Option<u64>,
+    #[prost(bool, tag = "21")]
+    pub terminated: bool,
+    #[prost(message, repeated, tag = "22")]
+    pub extra_fd_outputs: ::prost::alloc::vec::Vec<ExtraFdOutput>,
+    #[prost(string, optional, tag = "23")]
+    pub stdout_base64: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "24")]
+    pub stderr_base64: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "25")]
+    pub merged_output_base64: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, tag = "26")]
+    pub stdin_truncated: bool,
+    #[prost(string, optional, tag = "27")]
+    pub tag: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint32, optional, tag = "28")]
+    pub attempt_count: ::core::option::Option<u32>,
+    #[prost(message, optional, tag = "29")]
+    pub error: ::core::option::Option<ErrorDetail>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExtraFdOutput {
+    #[prost(uint32, tag = "1")]
+    pub fd: u32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bool, tag = "3")]
+    pub truncated: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunCommandsRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub requests: ::prost::alloc::vec::Vec<RunCommandRequest>,
+    #[prost(bool, tag = "2")]
+    pub stop_on_first_failure: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunCommandsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub responses: ::prost::alloc::vec::Vec<RunCommandResponse>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DryRunPlan {
+    #[prost(string, tag = "1")]
+    pub resolved_cli_path: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "2")]
+    pub args: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "3")]
+    pub cwd: ::prost::alloc::string::String,
+    #[prost(map = "string, string", tag = "4")]
+    pub effective_env:
+        ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "5")]
+    pub rejected_env_vars: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DescribeServerRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DescribeServerResponse {
+    #[prost(string, tag = "1")]
+    pub default_cli_path: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub version: ::prost::alloc::string::String,
+    #[prost(uint32, optional, tag = "3")]
+    pub concurrency_limit: ::core::option::Option<u32>,
+    #[prost(bool, tag = "4")]
+    pub env_allowlist_active: bool,
+    #[prost(bool, tag = "5")]
+    pub cli_override_allowed: bool,
+    #[prost(enumeration = "CliPathSource", tag = "6")]
+    pub default_cli_path_source: i32,
+    #[prost(string, repeated, tag = "7")]
+    pub default_env_keys: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CliPathSource {
+    Unspecified = 0,
+    Flag = 1,
+    Env = 2,
+    Sibling = 3,
+    Default = 4,
+}
+impl CliPathSource {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            CliPathSource::Unspecified => "CLI_PATH_SOURCE_UNSPECIFIED",
+            CliPathSource::Flag => "CLI_PATH_SOURCE_FLAG",
+            CliPathSource::Env => "CLI_PATH_SOURCE_ENV",
+            CliPathSource::Sibling => "CLI_PATH_SOURCE_SIBLING",
+            CliPathSource::Default => "CLI_PATH_SOURCE_DEFAULT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CLI_PATH_SOURCE_UNSPECIFIED" => Some(Self::Unspecified),
+            "CLI_PATH_SOURCE_FLAG" => Some(Self::Flag),
+            "CLI_PATH_SOURCE_ENV" => Some(Self::Env),
+            "CLI_PATH_SOURCE_SIBLING" => Some(Self::Sibling),
+            "CLI_PATH_SOURCE_DEFAULT" => Some(Self::Default),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamCommandRequest {
+    #[prost(string, repeated, tag = "1")]
+    pub args: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "2")]
+    pub cwd: ::prost::alloc::string::String,
+    #[prost(uint32, optional, tag = "3")]
+    pub chunk_size: ::core::option::Option<u32>,
+    #[prost(bool, optional, tag = "4")]
+    pub split_lines: ::core::option::Option<bool>,
+    #[prost(bool, optional, tag = "5")]
+    pub global_sequence: ::core::option::Option<bool>,
+    #[prost(uint32, optional, tag = "6")]
+    pub flush_interval_ms: ::core::option::Option<u32>,
+    #[prost(string, optional, tag = "7")]
+    pub progress_prefix: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Stream {
+    Unspecified = 0,
+    Stdout = 1,
+    Stderr = 2,
+}
+impl Stream {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Stream::Unspecified => "STREAM_UNSPECIFIED",
+            Stream::Stdout => "STREAM_STDOUT",
+            Stream::Stderr => "STREAM_STDERR",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "STREAM_UNSPECIFIED" => Some(Self::Unspecified),
+            "STREAM_STDOUT" => Some(Self::Stdout),
+            "STREAM_STDERR" => Some(Self::Stderr),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProgressEvent {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamCommandChunk {
+    #[prost(uint64, tag = "4")]
+    pub seq: u64,
+    #[prost(enumeration = "Stream", tag = "5")]
+    pub stream: i32,
+    #[prost(oneof = "stream_command_chunk::Chunk", tags = "1, 2, 3, 6")]
+    pub chunk: ::core::option::Option<stream_command_chunk::Chunk>,
+}
+/// Nested message and enum types in `StreamCommandChunk`.
+pub mod stream_command_chunk {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Chunk {
+        #[prost(bytes, tag = "1")]
+        Stdout(::prost::alloc::vec::Vec<u8>),
+        #[prost(bytes, tag = "2")]
+        Stderr(::prost::alloc::vec::Vec<u8>),
+        #[prost(int32, tag = "3")]
+        ExitCode(i32),
+        #[prost(message, tag = "6")]
+        Progress(super::ProgressEvent),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PingRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PingResponse {
+    #[prost(int64, tag = "1")]
+    pub timestamp_millis: i64,
+    #[prost(string, tag = "2")]
+    pub version: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelCommandRequest {
+    #[prost(string, tag = "1")]
+    pub request_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelCommandResponse {
+    #[prost(bool, tag = "1")]
+    pub found: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListRunningRequest {
+    #[prost(bool, tag = "1")]
+    pub redact_args: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RunningCommand {
+    #[prost(string, optional, tag = "1")]
+    pub request_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "2")]
+    pub args: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint32, tag = "3")]
+    pub pid: u32,
+    #[prost(uint64, tag = "4")]
+    pub elapsed_ms: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListRunningResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub commands: ::prost::alloc::vec::Vec<RunningCommand>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShutdownServerRequest {
+    #[prost(string, optional, tag = "1")]
+    pub token: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShutdownServerResponse {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ErrorDetail {
+    #[prost(enumeration = "ErrorCode", tag = "1")]
+    pub code: i32,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ErrorCode {
+    Unspecified = 0,
+    SpawnFailed = 1,
+    InvalidArgument = 2,
+    PermissionDenied = 3,
+    ResourceExhausted = 4,
+    CliUnavailable = 5,
+    Internal = 6,
+    ShuttingDown = 7,
+}
+impl ErrorCode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ErrorCode::Unspecified => "ERROR_CODE_UNSPECIFIED",
+            ErrorCode::SpawnFailed => "SPAWN_FAILED",
+            ErrorCode::InvalidArgument => "INVALID_ARGUMENT",
+            ErrorCode::PermissionDenied => "PERMISSION_DENIED",
+            ErrorCode::ResourceExhausted => "RESOURCE_EXHAUSTED",
+            ErrorCode::CliUnavailable => "CLI_UNAVAILABLE",
+            ErrorCode::Internal => "INTERNAL",
+            ErrorCode::ShuttingDown => "SHUTTING_DOWN",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ERROR_CODE_UNSPECIFIED" => Some(Self::Unspecified),
+            "SPAWN_FAILED" => Some(Self::SpawnFailed),
+            "INVALID_ARGUMENT" => Some(Self::InvalidArgument),
+            "PERMISSION_DENIED" => Some(Self::PermissionDenied),
+            "RESOURCE_EXHAUSTED" => Some(Self::ResourceExhausted),
+            "CLI_UNAVAILABLE" => Some(Self::CliUnavailable),
+            "INTERNAL" => Some(Self::Internal),
+            "SHUTTING_DOWN" => Some(Self::ShuttingDown),
+            _ => None,
+        }
+    }
+}
+/// Generated client implementations.
+pub mod codex_cli_client {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value
+    )]
+    use tonic::codegen::http::Uri;
+    use tonic::codegen::*;
+    #[derive(Debug, Clone)]
+    pub struct CodexCliClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl CodexCliClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> CodexCliClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::Body>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> CodexCliClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                    http::Request<tonic::body::Body>,
+                    Response = http::Response<
+                        <T as tonic::client::GrpcService<tonic::body::Body>>::ResponseBody,
+                    >,
+                >,
+            <T as tonic::codegen::Service<http::Request<tonic::body::Body>>>::Error:
+                Into<StdError> + std::marker::Send + std::marker::Sync,
+        {
+            CodexCliClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        pub async fn run_command(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RunCommandRequest>,
+        ) -> std::result::Result<tonic::Response<super::RunCommandResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/codex.cli_bridge.v1.CodexCli/RunCommand");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("codex.cli_bridge.v1.CodexCli", "RunCommand"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn stream_command(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamCommandRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::StreamCommandChunk>>,
+            tonic::Status,
+        > {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/codex.cli_bridge.v1.CodexCli/StreamCommand",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "codex.cli_bridge.v1.CodexCli",
+                "StreamCommand",
+            ));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        pub async fn run_interactive_command(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::InteractiveCommandInput>,
+        ) -> std::result::Result<tonic::Response<super::RunCommandResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/codex.cli_bridge.v1.CodexCli/RunInteractiveCommand",
+            );
+            let mut req = request.into_streaming_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "codex.cli_bridge.v1.CodexCli",
+                "RunInteractiveCommand",
+            ));
+            self.inner.client_streaming(req, path, codec).await
+        }
+        pub async fn describe_server(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DescribeServerRequest>,
+        ) -> std::result::Result<tonic::Response<super::DescribeServerResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/codex.cli_bridge.v1.CodexCli/DescribeServer",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "codex.cli_bridge.v1.CodexCli",
+                "DescribeServer",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn ping(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PingRequest>,
+        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/codex.cli_bridge.v1.CodexCli/Ping");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("codex.cli_bridge.v1.CodexCli", "Ping"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn cancel_command(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelCommandRequest>,
+        ) -> std::result::Result<tonic::Response<super::CancelCommandResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/codex.cli_bridge.v1.CodexCli/CancelCommand",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "codex.cli_bridge.v1.CodexCli",
+                "CancelCommand",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_running(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListRunningRequest>,
+        ) -> std::result::Result<tonic::Response<super::ListRunningResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/codex.cli_bridge.v1.CodexCli/ListRunning",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "codex.cli_bridge.v1.CodexCli",
+                "ListRunning",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn run_commands(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RunCommandsRequest>,
+        ) -> std::result::Result<tonic::Response<super::RunCommandsResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/codex.cli_bridge.v1.CodexCli/RunCommands",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "codex.cli_bridge.v1.CodexCli",
+                "RunCommands",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn shutdown_server(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ShutdownServerRequest>,
+        ) -> std::result::Result<tonic::Response<super::ShutdownServerResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::unknown(format!("Service was not ready: {}", e.into()))
+            })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/codex.cli_bridge.v1.CodexCli/ShutdownServer",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "codex.cli_bridge.v1.CodexCli",
+                "ShutdownServer",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod codex_cli_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with CodexCliServer.
+    #[async_trait]
+    pub trait CodexCli: std::marker::Send + std::marker::Sync + 'static {
+        async fn run_command(
+            &self,
+            request: tonic::Request<super::RunCommandRequest>,
+        ) -> std::result::Result<tonic::Response<super::RunCommandResponse>, tonic::Status>;
+        /// Server streaming response type for the StreamCommand method.
+        type StreamCommandStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::StreamCommandChunk, tonic::Status>,
+            > + std::marker::Send
+            + 'static;
+        async fn stream_command(
+            &self,
+            request: tonic::Request<super::StreamCommandRequest>,
+        ) -> std::result::Result<tonic::Response<Self::StreamCommandStream>, tonic::Status>;
+        async fn run_interactive_command(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::InteractiveCommandInput>>,
+        ) -> std::result::Result<tonic::Response<super::RunCommandResponse>, tonic::Status>;
+        async fn describe_server(
+            &self,
+            request: tonic::Request<super::DescribeServerRequest>,
+        ) -> std::result::Result<tonic::Response<super::DescribeServerResponse>, tonic::Status>;
+        async fn ping(
+            &self,
+            request: tonic::Request<super::PingRequest>,
+        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status>;
+        async fn cancel_command(
+            &self,
+            request: tonic::Request<super::CancelCommandRequest>,
+        ) -> std::result::Result<tonic::Response<super::CancelCommandResponse>, tonic::Status>;
+        async fn list_running(
+            &self,
+            request: tonic::Request<super::ListRunningRequest>,
+        ) -> std::result::Result<tonic::Response<super::ListRunningResponse>, tonic::Status>;
+        async fn run_commands(
+            &self,
+            request: tonic::Request<super::RunCommandsRequest>,
+        ) -> std::result::Result<tonic::Response<super::RunCommandsResponse>, tonic::Status>;
+        async fn shutdown_server(
+            &self,
+            request: tonic::Request<super::ShutdownServerRequest>,
+        ) -> std::result::Result<tonic::Response<super::ShutdownServerResponse>, tonic::Status>;
+    }
+    #[derive(Debug)]
+    pub struct CodexCliServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> CodexCliServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(inner: T, interceptor: F) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for CodexCliServer<T>
+    where
+        T: CodexCli,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::Body>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/codex.cli_bridge.v1.CodexCli/RunCommand" => {
+                    #[allow(non_camel_case_types)]
+                    struct RunCommandSvc<T: CodexCli>(pub Arc<T>);
+                    impl<T: CodexCli> tonic::server::UnaryService<super::RunCommandRequest>
+                        for RunCommandSvc<T>
+                    {
+                        type Response = super::RunCommandResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RunCommandRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as CodexCli>::run_command(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RunCommandSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/codex.cli_bridge.v1.CodexCli/StreamCommand" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamCommandSvc<T: CodexCli>(pub Arc<T>);
+                    impl<T: CodexCli>
+                        tonic::server::ServerStreamingService<super::StreamCommandRequest>
+                        for StreamCommandSvc<T>
+                    {
+                        type Response = super::StreamCommandChunk;
+                        type ResponseStream = T::StreamCommandStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamCommandRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CodexCli>::stream_command(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = StreamCommandSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/codex.cli_bridge.v1.CodexCli/RunInteractiveCommand" => {
+                    #[allow(non_camel_case_types)]
+                    struct RunInteractiveCommandSvc<T: CodexCli>(pub Arc<T>);
+                    impl<T: CodexCli>
+                        tonic::server::ClientStreamingService<super::InteractiveCommandInput>
+                        for RunInteractiveCommandSvc<T>
+                    {
+                        type Response = super::RunCommandResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<tonic::Streaming<super::InteractiveCommandInput>>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CodexCli>::run_interactive_command(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RunInteractiveCommandSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.client_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/codex.cli_bridge.v1.CodexCli/DescribeServer" => {
+                    #[allow(non_camel_case_types)]
+                    struct DescribeServerSvc<T: CodexCli>(pub Arc<T>);
+                    impl<T: CodexCli> tonic::server::UnaryService<super::DescribeServerRequest>
+                        for DescribeServerSvc<T>
+                    {
+                        type Response = super::DescribeServerResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DescribeServerRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CodexCli>::describe_server(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DescribeServerSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/codex.cli_bridge.v1.CodexCli/Ping" => {
+                    #[allow(non_camel_case_types)]
+                    struct PingSvc<T: CodexCli>(pub Arc<T>);
+                    impl<T: CodexCli> tonic::server::UnaryService<super::PingRequest> for PingSvc<T> {
+                        type Response = super::PingResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(&mut self, request: tonic::Request<super::PingRequest>) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as CodexCli>::ping(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = PingSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/codex.cli_bridge.v1.CodexCli/CancelCommand" => {
+                    #[allow(non_camel_case_types)]
+                    struct CancelCommandSvc<T: CodexCli>(pub Arc<T>);
+                    impl<T: CodexCli> tonic::server::UnaryService<super::CancelCommandRequest>
+                        for CancelCommandSvc<T>
+                    {
+                        type Response = super::CancelCommandResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelCommandRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as CodexCli>::cancel_command(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CancelCommandSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/codex.cli_bridge.v1.CodexCli/ListRunning" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListRunningSvc<T: CodexCli>(pub Arc<T>);
+                    impl<T: CodexCli> tonic::server::UnaryService<super::ListRunningRequest>
+                        for ListRunningSvc<T>
+                    {
+                        type Response = super::ListRunningResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListRunningRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as CodexCli>::list_running(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListRunningSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/codex.cli_bridge.v1.CodexCli/RunCommands" => {
+                    #[allow(non_camel_case_types)]
+                    struct RunCommandsSvc<T: CodexCli>(pub Arc<T>);
+                    impl<T: CodexCli> tonic::server::UnaryService<super::RunCommandsRequest>
+                        for RunCommandsSvc<T>
+                    {
+                        type Response = super::RunCommandsResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RunCommandsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as CodexCli>::run_commands(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RunCommandsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/codex.cli_bridge.v1.CodexCli/ShutdownServer" => {
+                    #[allow(non_camel_case_types)]
+                    struct ShutdownServerSvc<T: CodexCli>(pub Arc<T>);
+                    impl<T: CodexCli> tonic::server::UnaryService<super::ShutdownServerRequest>
+                        for ShutdownServerSvc<T>
+                    {
+                        type Response = super::ShutdownServerResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ShutdownServerRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CodexCli>::shutdown_server(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ShutdownServerSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => Box::pin(async move {
+                    let mut response = http::Response::new(tonic::body::Body::default());
+                    let headers = response.headers_mut();
+                    headers.insert(
+                        tonic::Status::GRPC_STATUS,
+                        (tonic::Code::Unimplemented as i32).into(),
+                    );
+                    headers.insert(
+                        http::header::CONTENT_TYPE,
+                        tonic::metadata::GRPC_CONTENT_TYPE,
+                    );
+                    Ok(response)
+                }),
+            }
+        }
+    }
+    impl<T> Clone for CodexCliServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    /// Generated gRPC service name
+    pub const SERVICE_NAME: &str = "codex.cli_bridge.v1.CodexCli";
+    impl<T> tonic::server::NamedService for CodexCliServer<T> {
+        const NAME: &'static str = SERVICE_NAME;
+    }
+}
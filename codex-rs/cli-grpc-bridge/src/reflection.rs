@@ -0,0 +1,1263 @@
+// This file is hand-constructed to mirror codex.cli_bridge.v1.proto for
+// tonic-reflection, since this crate's codegen (see examples/generate-proto.rs)
+// is run manually and its output checked in rather than produced by a build.rs;
+// building the descriptor set in Rust avoids a protoc build dependency. Keep it
+// in sync with codex.cli_bridge.v1.proto by hand, the same way codex.cli_bridge.v1.rs is.
+
+/// Builds the `FileDescriptorProto` for `codex.cli_bridge.v1.proto`, used by
+/// `tonic-reflection` when the server is started with `--enable-reflection`.
+pub(crate) fn file_descriptor_proto() -> prost_types::FileDescriptorProto {
+    prost_types::FileDescriptorProto {
+        name: Some("codex.cli_bridge.v1.proto".to_string()),
+        package: Some("codex.cli_bridge.v1".to_string()),
+        syntax: Some("proto3".to_string()),
+        message_type: vec![
+            prost_types::DescriptorProto {
+                name: Some("RunCommandRequest".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("args".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("cwd".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("timeout_ms".to_string()),
+                        number: Some(3),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("env".to_string()),
+                        number: Some(4),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Message as i32),
+                        type_name: Some(".codex.cli_bridge.v1.RunCommandRequest.EnvEntry".to_string()),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stdin".to_string()),
+                        number: Some(5),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bytes as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("cli_path".to_string()),
+                        number: Some(6),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("merge_streams".to_string()),
+                        number: Some(7),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("dry_run".to_string()),
+                        number: Some(8),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("decode_utf8".to_string()),
+                        number: Some(9),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stdout_file_path".to_string()),
+                        number: Some(10),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("max_cpu_seconds".to_string()),
+                        number: Some(11),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("max_memory_bytes".to_string()),
+                        number: Some(12),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("max_open_files".to_string()),
+                        number: Some(13),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("inherit_server_env".to_string()),
+                        number: Some(14),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("request_id".to_string()),
+                        number: Some(15),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("nice".to_string()),
+                        number: Some(16),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Int32 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stdin_file_path".to_string()),
+                        number: Some(17),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("clear_env".to_string()),
+                        number: Some(18),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("extra_fds".to_string()),
+                        number: Some(19),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint32 as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("encode_base64".to_string()),
+                        number: Some(20),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("client_id".to_string()),
+                        number: Some(21),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("cpu_affinity".to_string()),
+                        number: Some(22),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint32 as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("expected_output_bytes".to_string()),
+                        number: Some(23),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("umask".to_string()),
+                        number: Some(24),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint32 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("tag".to_string()),
+                        number: Some(25),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("retry_on_exit_codes".to_string()),
+                        number: Some(26),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Int32 as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("max_retries".to_string()),
+                        number: Some(27),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint32 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("tail_bytes".to_string()),
+                        number: Some(28),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                ],
+                nested_type: vec![
+                    prost_types::DescriptorProto {
+                        name: Some("EnvEntry".to_string()),
+                        field: vec![
+                            prost_types::FieldDescriptorProto {
+                                name: Some("key".to_string()),
+                                number: Some(1),
+                                label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                                r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                                ..Default::default()
+                            },
+                            prost_types::FieldDescriptorProto {
+                                name: Some("value".to_string()),
+                                number: Some(2),
+                                label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                                r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                                ..Default::default()
+                            },
+                        ],
+                        options: Some(prost_types::MessageOptions {
+                            map_entry: Some(true),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("InteractiveCommandInput".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("start".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Message as i32),
+                        type_name: Some(".codex.cli_bridge.v1.RunCommandRequest".to_string()),
+                        oneof_index: Some(0),
+                        proto3_optional: Some(false),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stdin_chunk".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bytes as i32),
+                        type_name: None,
+                        oneof_index: Some(0),
+                        proto3_optional: Some(false),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("close_stdin".to_string()),
+                        number: Some(3),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        oneof_index: Some(0),
+                        proto3_optional: Some(false),
+                        ..Default::default()
+                    },
+                ],
+                oneof_decl: vec![
+                    prost_types::OneofDescriptorProto {
+                        name: Some("input".to_string()),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("RunCommandResponse".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stdout".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bytes as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stderr".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bytes as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("exit_code".to_string()),
+                        number: Some(3),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Int32 as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stdout_truncated".to_string()),
+                        number: Some(4),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stderr_truncated".to_string()),
+                        number: Some(5),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("pid".to_string()),
+                        number: Some(6),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint32 as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("terminating_signal".to_string()),
+                        number: Some(7),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Int32 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("merged_output".to_string()),
+                        number: Some(8),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bytes as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("merged_output_truncated".to_string()),
+                        number: Some(9),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("dry_run".to_string()),
+                        number: Some(10),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Message as i32),
+                        type_name: Some(".codex.cli_bridge.v1.DryRunPlan".to_string()),
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stdout_utf8".to_string()),
+                        number: Some(11),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stderr_utf8".to_string()),
+                        number: Some(12),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("merged_output_utf8".to_string()),
+                        number: Some(13),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stdout_bytes_written".to_string()),
+                        number: Some(14),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("started_at_millis".to_string()),
+                        number: Some(15),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Int64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("finished_at_millis".to_string()),
+                        number: Some(16),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Int64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("user_cpu_ms".to_string()),
+                        number: Some(17),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("system_cpu_ms".to_string()),
+                        number: Some(18),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("max_rss_kb".to_string()),
+                        number: Some(19),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("queue_wait_ms".to_string()),
+                        number: Some(20),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("terminated".to_string()),
+                        number: Some(21),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("extra_fd_outputs".to_string()),
+                        number: Some(22),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Message as i32),
+                        type_name: Some(".codex.cli_bridge.v1.ExtraFdOutput".to_string()),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stdout_base64".to_string()),
+                        number: Some(23),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stderr_base64".to_string()),
+                        number: Some(24),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("merged_output_base64".to_string()),
+                        number: Some(25),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stdin_truncated".to_string()),
+                        number: Some(26),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("tag".to_string()),
+                        number: Some(27),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("attempt_count".to_string()),
+                        number: Some(28),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint32 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("error".to_string()),
+                        number: Some(29),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Message as i32),
+                        type_name: Some(".codex.cli_bridge.v1.ErrorDetail".to_string()),
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("ExtraFdOutput".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("fd".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint32 as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("data".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bytes as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("truncated".to_string()),
+                        number: Some(3),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("RunCommandsRequest".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("requests".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Message as i32),
+                        type_name: Some(".codex.cli_bridge.v1.RunCommandRequest".to_string()),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stop_on_first_failure".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("RunCommandsResponse".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("responses".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Message as i32),
+                        type_name: Some(".codex.cli_bridge.v1.RunCommandResponse".to_string()),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("DryRunPlan".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("resolved_cli_path".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("args".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("cwd".to_string()),
+                        number: Some(3),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("effective_env".to_string()),
+                        number: Some(4),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Message as i32),
+                        type_name: Some(".codex.cli_bridge.v1.DryRunPlan.EffectiveEnvEntry".to_string()),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("rejected_env_vars".to_string()),
+                        number: Some(5),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                ],
+                nested_type: vec![
+                    prost_types::DescriptorProto {
+                        name: Some("EffectiveEnvEntry".to_string()),
+                        field: vec![
+                            prost_types::FieldDescriptorProto {
+                                name: Some("key".to_string()),
+                                number: Some(1),
+                                label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                                r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                                ..Default::default()
+                            },
+                            prost_types::FieldDescriptorProto {
+                                name: Some("value".to_string()),
+                                number: Some(2),
+                                label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                                r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                                ..Default::default()
+                            },
+                        ],
+                        options: Some(prost_types::MessageOptions {
+                            map_entry: Some(true),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("DescribeServerRequest".to_string()),
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("DescribeServerResponse".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("default_cli_path".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("version".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("concurrency_limit".to_string()),
+                        number: Some(3),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint32 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("env_allowlist_active".to_string()),
+                        number: Some(4),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("cli_override_allowed".to_string()),
+                        number: Some(5),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("default_cli_path_source".to_string()),
+                        number: Some(6),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Enum as i32),
+                        type_name: Some(".codex.cli_bridge.v1.CliPathSource".to_string()),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("default_env_keys".to_string()),
+                        number: Some(7),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("PingRequest".to_string()),
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("PingResponse".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("timestamp_millis".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Int64 as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("version".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("CancelCommandRequest".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("request_id".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("CancelCommandResponse".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("found".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("ListRunningRequest".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("redact_args".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("RunningCommand".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("request_id".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("args".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("pid".to_string()),
+                        number: Some(3),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint32 as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("elapsed_ms".to_string()),
+                        number: Some(4),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("ListRunningResponse".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("commands".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Message as i32),
+                        type_name: Some(".codex.cli_bridge.v1.RunningCommand".to_string()),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("ShutdownServerRequest".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("token".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("ShutdownServerResponse".to_string()),
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("StreamCommandRequest".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("args".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Repeated as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("cwd".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("chunk_size".to_string()),
+                        number: Some(3),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint32 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("split_lines".to_string()),
+                        number: Some(4),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("global_sequence".to_string()),
+                        number: Some(5),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bool as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("flush_interval_ms".to_string()),
+                        number: Some(6),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint32 as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("progress_prefix".to_string()),
+                        number: Some(7),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        proto3_optional: Some(true),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("ProgressEvent".to_string()),
+                field: vec![prost_types::FieldDescriptorProto {
+                    name: Some("message".to_string()),
+                    number: Some(1),
+                    label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                    r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                    type_name: None,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("StreamCommandChunk".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stdout".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bytes as i32),
+                        type_name: None,
+                        oneof_index: Some(0),
+                        proto3_optional: Some(false),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stderr".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Bytes as i32),
+                        type_name: None,
+                        oneof_index: Some(0),
+                        proto3_optional: Some(false),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("exit_code".to_string()),
+                        number: Some(3),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Int32 as i32),
+                        type_name: None,
+                        oneof_index: Some(0),
+                        proto3_optional: Some(false),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("progress".to_string()),
+                        number: Some(6),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Message as i32),
+                        type_name: Some(".codex.cli_bridge.v1.ProgressEvent".to_string()),
+                        oneof_index: Some(0),
+                        proto3_optional: Some(false),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("seq".to_string()),
+                        number: Some(4),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Uint64 as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("stream".to_string()),
+                        number: Some(5),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Enum as i32),
+                        type_name: Some(".codex.cli_bridge.v1.Stream".to_string()),
+                        ..Default::default()
+                    },
+                ],
+                oneof_decl: vec![
+                    prost_types::OneofDescriptorProto {
+                        name: Some("chunk".to_string()),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::DescriptorProto {
+                name: Some("ErrorDetail".to_string()),
+                field: vec![
+                    prost_types::FieldDescriptorProto {
+                        name: Some("code".to_string()),
+                        number: Some(1),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::Enum as i32),
+                        type_name: Some(".codex.cli_bridge.v1.ErrorCode".to_string()),
+                        ..Default::default()
+                    },
+                    prost_types::FieldDescriptorProto {
+                        name: Some("message".to_string()),
+                        number: Some(2),
+                        label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(prost_types::field_descriptor_proto::Type::String as i32),
+                        type_name: None,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+        ],
+        enum_type: vec![
+            prost_types::EnumDescriptorProto {
+                name: Some("ErrorCode".to_string()),
+                value: vec![
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("ERROR_CODE_UNSPECIFIED".to_string()),
+                        number: Some(0),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("SPAWN_FAILED".to_string()),
+                        number: Some(1),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("INVALID_ARGUMENT".to_string()),
+                        number: Some(2),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("PERMISSION_DENIED".to_string()),
+                        number: Some(3),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("RESOURCE_EXHAUSTED".to_string()),
+                        number: Some(4),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("CLI_UNAVAILABLE".to_string()),
+                        number: Some(5),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("INTERNAL".to_string()),
+                        number: Some(6),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("SHUTTING_DOWN".to_string()),
+                        number: Some(7),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::EnumDescriptorProto {
+                name: Some("Stream".to_string()),
+                value: vec![
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("STREAM_UNSPECIFIED".to_string()),
+                        number: Some(0),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("STREAM_STDOUT".to_string()),
+                        number: Some(1),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("STREAM_STDERR".to_string()),
+                        number: Some(2),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            prost_types::EnumDescriptorProto {
+                name: Some("CliPathSource".to_string()),
+                value: vec![
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("CLI_PATH_SOURCE_UNSPECIFIED".to_string()),
+                        number: Some(0),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("CLI_PATH_SOURCE_FLAG".to_string()),
+                        number: Some(1),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("CLI_PATH_SOURCE_ENV".to_string()),
+                        number: Some(2),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("CLI_PATH_SOURCE_SIBLING".to_string()),
+                        number: Some(3),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("CLI_PATH_SOURCE_DEFAULT".to_string()),
+                        number: Some(4),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+        ],
+        service: vec![prost_types::ServiceDescriptorProto {
+            name: Some("CodexCli".to_string()),
+            method: vec![
+                prost_types::MethodDescriptorProto {
+                    name: Some("RunCommand".to_string()),
+                    input_type: Some(".codex.cli_bridge.v1.RunCommandRequest".to_string()),
+                    output_type: Some(".codex.cli_bridge.v1.RunCommandResponse".to_string()),
+                    ..Default::default()
+                },
+                prost_types::MethodDescriptorProto {
+                    name: Some("StreamCommand".to_string()),
+                    input_type: Some(".codex.cli_bridge.v1.StreamCommandRequest".to_string()),
+                    output_type: Some(".codex.cli_bridge.v1.StreamCommandChunk".to_string()),
+                    server_streaming: Some(true),
+                    ..Default::default()
+                },
+                prost_types::MethodDescriptorProto {
+                    name: Some("RunInteractiveCommand".to_string()),
+                    input_type: Some(".codex.cli_bridge.v1.InteractiveCommandInput".to_string()),
+                    output_type: Some(".codex.cli_bridge.v1.RunCommandResponse".to_string()),
+                    client_streaming: Some(true),
+                    ..Default::default()
+                },
+                prost_types::MethodDescriptorProto {
+                    name: Some("DescribeServer".to_string()),
+                    input_type: Some(".codex.cli_bridge.v1.DescribeServerRequest".to_string()),
+                    output_type: Some(".codex.cli_bridge.v1.DescribeServerResponse".to_string()),
+                    ..Default::default()
+                },
+                prost_types::MethodDescriptorProto {
+                    name: Some("Ping".to_string()),
+                    input_type: Some(".codex.cli_bridge.v1.PingRequest".to_string()),
+                    output_type: Some(".codex.cli_bridge.v1.PingResponse".to_string()),
+                    ..Default::default()
+                },
+                prost_types::MethodDescriptorProto {
+                    name: Some("CancelCommand".to_string()),
+                    input_type: Some(".codex.cli_bridge.v1.CancelCommandRequest".to_string()),
+                    output_type: Some(".codex.cli_bridge.v1.CancelCommandResponse".to_string()),
+                    ..Default::default()
+                },
+                prost_types::MethodDescriptorProto {
+                    name: Some("ListRunning".to_string()),
+                    input_type: Some(".codex.cli_bridge.v1.ListRunningRequest".to_string()),
+                    output_type: Some(".codex.cli_bridge.v1.ListRunningResponse".to_string()),
+                    ..Default::default()
+                },
+                prost_types::MethodDescriptorProto {
+                    name: Some("RunCommands".to_string()),
+                    input_type: Some(".codex.cli_bridge.v1.RunCommandsRequest".to_string()),
+                    output_type: Some(".codex.cli_bridge.v1.RunCommandsResponse".to_string()),
+                    ..Default::default()
+                },
+                prost_types::MethodDescriptorProto {
+                    name: Some("ShutdownServer".to_string()),
+                    input_type: Some(".codex.cli_bridge.v1.ShutdownServerRequest".to_string()),
+                    output_type: Some(".codex.cli_bridge.v1.ShutdownServerResponse".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
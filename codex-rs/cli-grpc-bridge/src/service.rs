@@ -0,0 +1,3762 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures::StreamExt;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Child;
+use tokio::process::Command;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use tonic::Code;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::Streaming;
+
+use crate::proto::DescribeServerRequest;
+use crate::proto::DescribeServerResponse;
+use crate::proto::ErrorCode;
+use crate::proto::ErrorDetail;
+use crate::proto::InteractiveCommandInput;
+use crate::proto::ProgressEvent;
+use crate::proto::RunCommandRequest;
+use crate::proto::RunCommandResponse;
+use crate::proto::RunCommandsRequest;
+use crate::proto::RunCommandsResponse;
+use crate::proto::Stream;
+use crate::proto::StreamCommandChunk;
+use crate::proto::StreamCommandRequest;
+use crate::proto::codex_cli_server::CodexCli;
+use crate::proto::interactive_command_input::Input;
+use crate::proto::stream_command_chunk::Chunk;
+use crate::process_group::set_own_process_group;
+
+/// Default number of bytes forwarded per [`StreamCommandChunk`] when the
+/// caller does not set `chunk_size`.
+const DEFAULT_STREAM_CHUNK_SIZE: usize = 8192;
+
+/// Wall-clock reading in milliseconds since the Unix epoch, saturating to 0
+/// if the system clock is set before it.
+fn unix_millis_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Builds a `Status` carrying an [`ErrorDetail`] in its binary
+/// `grpc-status-details-bin` metadata, so a client that depends on this
+/// crate's proto types can decode `error_code` via `Status::details()`
+/// instead of pattern-matching `message`.
+fn status_with_detail(code: Code, error_code: ErrorCode, message: impl Into<String>) -> Status {
+    let message = message.into();
+    let detail = ErrorDetail {
+        code: error_code as i32,
+        message: message.clone(),
+    };
+    Status::with_details(code, message, prost::Message::encode_to_vec(&detail).into())
+}
+
+fn invalid_argument_detail(message: impl Into<String>) -> Status {
+    status_with_detail(Code::InvalidArgument, ErrorCode::InvalidArgument, message)
+}
+
+fn permission_denied_detail(message: impl Into<String>) -> Status {
+    status_with_detail(Code::PermissionDenied, ErrorCode::PermissionDenied, message)
+}
+
+fn resource_exhausted_detail(message: impl Into<String>) -> Status {
+    status_with_detail(Code::ResourceExhausted, ErrorCode::ResourceExhausted, message)
+}
+
+fn cli_unavailable_detail(message: impl Into<String>) -> Status {
+    status_with_detail(Code::FailedPrecondition, ErrorCode::CliUnavailable, message)
+}
+
+fn spawn_failed_detail(message: impl Into<String>) -> Status {
+    status_with_detail(Code::Internal, ErrorCode::SpawnFailed, message)
+}
+
+fn internal_detail(message: impl Into<String>) -> Status {
+    status_with_detail(Code::Internal, ErrorCode::Internal, message)
+}
+
+fn shutting_down_detail(message: impl Into<String>) -> Status {
+    status_with_detail(Code::Unavailable, ErrorCode::ShuttingDown, message)
+}
+
+/// Converts an RPC-level `Status` (as `run_command` returns on a validation,
+/// spawn, or concurrency-limit failure) into a [`RunCommandResponse`] with
+/// only `error` set, so `run_commands` can report a per-item failure inline
+/// instead of discarding every response already collected for the rest of
+/// the batch. Decodes the `ErrorDetail` attached by [`status_with_detail`]
+/// when present, falling back to `ERROR_CODE_UNSPECIFIED` with the status's
+/// own message otherwise.
+fn run_command_response_for_status(status: &Status) -> RunCommandResponse {
+    let bytes = status.details();
+    let detail = (!bytes.is_empty())
+        .then(|| <ErrorDetail as prost::Message>::decode(bytes).ok())
+        .flatten()
+        .unwrap_or_else(|| ErrorDetail {
+            code: ErrorCode::Unspecified as i32,
+            message: status.message().to_string(),
+        });
+    RunCommandResponse { error: Some(detail), ..Default::default() }
+}
+
+/// Whether `err` (from `Command::spawn`) is a transient, load-related
+/// failure worth retrying, as opposed to a misconfiguration like a missing
+/// or non-executable binary that will never succeed on retry.
+#[cfg(unix)]
+fn is_transient_spawn_error(err: &std::io::Error) -> bool {
+    matches!(err.raw_os_error(), Some(libc::EAGAIN) | Some(libc::ENOMEM))
+}
+
+#[cfg(not(unix))]
+fn is_transient_spawn_error(_err: &std::io::Error) -> bool {
+    false
+}
+
+/// Implementation of the `CodexCli` gRPC service: spawns the configured
+/// `codex` CLI binary and returns or streams its output.
+pub struct CodexCliService {
+    cli_path: PathBuf,
+    env_allowlist: Option<HashSet<String>>,
+    reject_disallowed_env: bool,
+    max_output_bytes: Option<usize>,
+    allowed_cwd_root: Option<PathBuf>,
+    concurrency_limit: Option<Arc<Semaphore>>,
+    concurrency_limit_value: Option<usize>,
+    acquire_timeout: Option<Duration>,
+    /// Maximum number of requests allowed to wait on a concurrency-limit
+    /// permit at once. Once `metrics.queue_depth()` reaches this, a new
+    /// request is rejected immediately instead of joining the wait. Unset
+    /// allows unbounded waiters, the prior behavior.
+    max_queue_depth: Option<usize>,
+    metrics: Arc<crate::metrics::Metrics>,
+    require_args: bool,
+    allowed_commands: Option<HashSet<String>>,
+    allow_cli_override: bool,
+    max_request_bytes: usize,
+    allowed_stdout_dir: Option<PathBuf>,
+    /// Root directory `stdin_file_path` is validated against, analogous to
+    /// `allowed_stdout_dir` for `stdout_file_path`.
+    allowed_stdin_dir: Option<PathBuf>,
+    allow_rlimits: bool,
+    allow_nice: bool,
+    /// Pids of in-flight children, keyed by the caller-supplied `request_id`,
+    /// so `cancel_command` can find one to kill. Entries are removed when
+    /// the owning `run_command`/`run_interactive_command` call returns.
+    active_children: Arc<Mutex<HashMap<String, u32>>>,
+    /// In-flight `run_command`/`run_interactive_command` invocations, keyed
+    /// by pid, for `list_running`. Unlike `active_children`, every
+    /// invocation is tracked here regardless of whether it set
+    /// `request_id`, since an operator dashboard should see everything the
+    /// bridge is doing right now.
+    running_commands: Arc<Mutex<HashMap<u32, RunningCommandEntry>>>,
+    /// Sink for the `--access-log` audit trail, when configured.
+    access_log_handle: Option<crate::access_log::AccessLogHandle>,
+    /// Whether `--log-stdin` was set, gating whether `req.stdin` is included
+    /// in access log entries.
+    log_stdin: bool,
+    /// Number of times `run_command` retries `Command::spawn` after a
+    /// transient `EAGAIN`/`ENOMEM` error, with exponential backoff between
+    /// attempts. See [`Self::spawn_with_retry`].
+    spawn_retries: u32,
+    /// Working directory used in [`Self::command_for`] when a request's
+    /// `cwd` is empty, instead of inheriting this server process's own
+    /// working directory. Unset preserves that inherited-cwd behavior.
+    default_cwd: Option<PathBuf>,
+    /// Lowercased `--redact-env` patterns. An environment variable whose
+    /// name contains any of these (case-insensitively) has its value
+    /// replaced with `***` in `--access-log` entries; see
+    /// [`Self::redact_env_for_log`].
+    redact_env: Vec<String>,
+    /// cgroup v2 directory spawned children are placed into via
+    /// `cgroup.procs`, for `--cgroup-parent`. Unset leaves children in
+    /// whatever cgroup this server process itself is in, the prior
+    /// behavior.
+    cgroup_parent: Option<PathBuf>,
+    /// How `cli_path` was resolved, surfaced by `describe_server` so an
+    /// operator can tell which rule supplied it.
+    cli_path_source: crate::proto::CliPathSource,
+    /// Whether a request's `extra_fds` may be honored, gating creation of
+    /// server-side pipes handed to the child before it execs.
+    allow_extra_fds: bool,
+    /// Maximum number of `RunCommand`/`RunInteractiveCommand` calls allowed
+    /// to run concurrently for a single `client_id`, for `--per-client-limit`.
+    /// Unset means no per-client limit on top of `--concurrency-limit`.
+    per_client_limit: Option<usize>,
+    /// In-flight call counts keyed by `client_id` (requests that leave it
+    /// unset all share the same `""` key), backing `--per-client-limit`. See
+    /// [`PerClientGuard`].
+    active_per_client: Arc<Mutex<HashMap<String, usize>>>,
+    /// Cancelled by `shutdown_server` to start the same graceful drain a
+    /// `SIGTERM` triggers; see [`crate::run_server`].
+    shutdown: CancellationToken,
+    /// Required to match a `shutdown_server` call's `token` exactly, when
+    /// set, for `--shutdown-token`. Unset lets any caller shut the server
+    /// down.
+    shutdown_token: Option<String>,
+    /// Whether a request's `cpu_affinity` may be honored, gating
+    /// `sched_setaffinity` pinning of the spawned child. Linux-only.
+    allow_cpu_affinity: bool,
+    /// Maximum number of bytes allowed in a request's `stdin`, checked
+    /// before `max_request_bytes` would otherwise let the whole message
+    /// through, so an oversized `stdin` gets a clearer error than the
+    /// generic decode failure. Unset means unbounded.
+    max_stdin_bytes: Option<usize>,
+    /// Whether `--strip-proxy-env` was set; see [`Self::strip_proxy_env`].
+    strip_proxy_env: bool,
+    /// `--term-grace-ms`: how long to wait after `SIGTERM` before escalating
+    /// to `SIGKILL` on a timeout kill. Unset sends `SIGKILL` immediately, the
+    /// prior behavior. See [`kill_child_with_grace`].
+    term_grace: Option<Duration>,
+    /// Token bucket backing `--spawn-rate`, shared across every
+    /// `run_command`/`run_interactive_command` call. Unset means no limit on
+    /// the rate of new spawns, the prior behavior. See
+    /// [`Self::acquire_spawn_rate_token`].
+    spawn_rate_limiter: Option<SpawnRateLimiter>,
+    /// Whether `--spawn-rate-reject` was set. When true, a caller that finds
+    /// the bucket empty is rejected immediately with `Status::resource_exhausted`
+    /// instead of waiting for a token to refill.
+    spawn_rate_reject: bool,
+    /// `--spawn-rate-wait-ms`: maximum time a caller will wait for a
+    /// `--spawn-rate` token before giving up with `Status::resource_exhausted`.
+    /// Unset waits as long as it takes. Ignored when `spawn_rate_reject` is
+    /// set.
+    spawn_rate_wait_bound: Option<Duration>,
+    /// Whether a request's `umask` may be honored, gating the `umask()`
+    /// `pre_exec` hook installed by [`crate::process_group::apply_umask`].
+    allow_umask: bool,
+    /// Whether `--reject-malformed-env` was set; see
+    /// [`Self::validate_env_keys`].
+    reject_malformed_env: bool,
+    /// `--default-env` pairs, applied to every spawned child before a
+    /// request's `env` is layered on top so a client can still override any
+    /// of them. Surfaced (names only) via `describe_server`.
+    default_env: HashMap<String, String>,
+}
+
+impl CodexCliService {
+    pub fn new(
+        cli_path: PathBuf,
+        env_allowlist: Option<HashSet<String>>,
+        reject_disallowed_env: bool,
+        max_output_bytes: Option<usize>,
+        allowed_cwd_root: Option<PathBuf>,
+        concurrency_limit: Option<usize>,
+        acquire_timeout: Option<Duration>,
+        metrics: Arc<crate::metrics::Metrics>,
+        require_args: bool,
+        allowed_commands: Option<HashSet<String>>,
+        allow_cli_override: bool,
+        max_request_bytes: usize,
+        allowed_stdout_dir: Option<PathBuf>,
+        allow_rlimits: bool,
+        allow_nice: bool,
+        access_log_handle: Option<crate::access_log::AccessLogHandle>,
+        log_stdin: bool,
+        spawn_retries: u32,
+        default_cwd: Option<PathBuf>,
+        redact_env: Vec<String>,
+        allowed_stdin_dir: Option<PathBuf>,
+        max_queue_depth: Option<usize>,
+        cgroup_parent: Option<PathBuf>,
+        cli_path_source: crate::proto::CliPathSource,
+        allow_extra_fds: bool,
+        per_client_limit: Option<usize>,
+        shutdown: CancellationToken,
+        shutdown_token: Option<String>,
+        allow_cpu_affinity: bool,
+        max_stdin_bytes: Option<usize>,
+        strip_proxy_env: bool,
+        term_grace: Option<Duration>,
+        spawn_rate: Option<f64>,
+        spawn_rate_reject: bool,
+        spawn_rate_wait_bound: Option<Duration>,
+        allow_umask: bool,
+        reject_malformed_env: bool,
+        default_env: HashMap<String, String>,
+    ) -> Self {
+        let redact_env = redact_env.into_iter().map(|pattern| pattern.to_lowercase()).collect();
+        Self {
+            cli_path,
+            env_allowlist,
+            reject_disallowed_env,
+            max_output_bytes,
+            allowed_cwd_root,
+            concurrency_limit: concurrency_limit.map(|limit| Arc::new(Semaphore::new(limit))),
+            concurrency_limit_value: concurrency_limit,
+            acquire_timeout,
+            max_queue_depth,
+            metrics,
+            require_args,
+            allowed_commands,
+            allow_cli_override,
+            max_request_bytes,
+            allowed_stdout_dir,
+            allowed_stdin_dir,
+            allow_rlimits,
+            allow_nice,
+            active_children: Arc::new(Mutex::new(HashMap::new())),
+            running_commands: Arc::new(Mutex::new(HashMap::new())),
+            access_log_handle,
+            log_stdin,
+            spawn_retries,
+            default_cwd,
+            redact_env,
+            cgroup_parent,
+            cli_path_source,
+            allow_extra_fds,
+            per_client_limit,
+            active_per_client: Arc::new(Mutex::new(HashMap::new())),
+            shutdown,
+            shutdown_token,
+            allow_cpu_affinity,
+            max_stdin_bytes,
+            strip_proxy_env,
+            term_grace,
+            spawn_rate_limiter: spawn_rate.map(SpawnRateLimiter::new),
+            spawn_rate_reject,
+            spawn_rate_wait_bound,
+            allow_umask,
+            reject_malformed_env,
+            default_env,
+        }
+    }
+
+    /// Removes `HTTP_PROXY`/`HTTPS_PROXY`/`ALL_PROXY` (and their lowercase
+    /// forms, since many CLIs honor either) from `command`'s environment
+    /// when `--strip-proxy-env` is set, so a locally-invoked command doesn't
+    /// pick up a proxy configured for the bridge's own outbound traffic.
+    /// Removes rather than merely not forwarding, so it also strips a
+    /// variable the child would otherwise inherit via `clear_env = false`
+    /// or `inherit_server_env`. No-op when unset, the prior behavior.
+    fn strip_proxy_env(&self, command: &mut Command) {
+        if !self.strip_proxy_env {
+            return;
+        }
+        for name in ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY", "http_proxy", "https_proxy", "all_proxy"] {
+            command.env_remove(name);
+        }
+    }
+
+    /// Builds the `env` map for an `--access-log` entry: every name in
+    /// `env`, with values redacted to `***` when the name contains (ignoring
+    /// case) any `--redact-env` pattern. The child itself still gets the
+    /// real values; this only governs what's written to disk.
+    fn redact_env_for_log(&self, env: &[(&str, &str)]) -> HashMap<String, String> {
+        env.iter()
+            .map(|(name, value)| {
+                let redacted = self
+                    .redact_env
+                    .iter()
+                    .any(|pattern| name.to_lowercase().contains(pattern));
+                let value = if redacted { "***" } else { value };
+                (name.to_string(), value.to_string())
+            })
+            .collect()
+    }
+
+    /// Resolves the CLI binary to spawn for a request, honoring a
+    /// per-request override only when `--allow-cli-override` is set.
+    fn resolve_cli_path<'a>(&'a self, requested: Option<&'a str>) -> Result<&'a std::path::Path, Status> {
+        match requested {
+            Some(path) if self.allow_cli_override => Ok(std::path::Path::new(path)),
+            Some(_) => Err(permission_denied_detail(
+                "per-request cli_path override requires the server to be started with --allow-cli-override",
+            )),
+            None => Ok(self.cli_path.as_path()),
+        }
+    }
+
+    /// Resolves `cli_path` to a concrete file, searching `$PATH` when it has
+    /// no directory component (the same rule `std::process::Command` itself
+    /// uses), so a bare `--cli-path codex` can still be validated.
+    fn locate_cli_path(&self, cli_path: &std::path::Path) -> Result<PathBuf, Status> {
+        if cli_path.components().count() > 1 {
+            return Ok(cli_path.to_path_buf());
+        }
+        let Some(path_var) = std::env::var_os("PATH") else {
+            return Err(cli_unavailable_detail(format!(
+                "cli_path {cli_path:?} has no directory component and $PATH is unset"
+            )));
+        };
+        std::env::split_paths(&path_var)
+            .map(|dir| dir.join(cli_path))
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| {
+                cli_unavailable_detail(format!("cli_path {cli_path:?} was not found in $PATH"))
+            })
+    }
+
+    /// Checks that `cli_path` exists and, on Unix, that it's executable,
+    /// before it's handed to [`Command::spawn`]. Run once per request rather
+    /// than relying on the spawn error, since a missing or non-executable
+    /// binary otherwise surfaces as an opaque `Status::internal` from deep
+    /// inside `std::process`.
+    fn validate_cli_path_is_executable(&self, cli_path: &std::path::Path) -> Result<(), Status> {
+        let resolved = self.locate_cli_path(cli_path)?;
+        let metadata = std::fs::metadata(&resolved).map_err(|err| {
+            if err.kind() == std::io::ErrorKind::NotFound {
+                cli_unavailable_detail(format!("cli_path {resolved:?} not found: {err}"))
+            } else {
+                cli_unavailable_detail(format!("cli_path {resolved:?} is not accessible: {err}"))
+            }
+        })?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if metadata.is_dir() || metadata.permissions().mode() & 0o111 == 0 {
+                return Err(cli_unavailable_detail(format!(
+                    "cli_path {resolved:?} is not an executable file"
+                )));
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            if metadata.is_dir() {
+                return Err(cli_unavailable_detail(format!(
+                    "cli_path {resolved:?} is not a file"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the same CLI-path resolution and executable check a request
+    /// would trigger, for `--verify-cli-on-startup` to call once at boot.
+    /// Surfaces the same failure a request would hit, just at startup
+    /// instead of on the first `run_command`.
+    pub(crate) fn verify_cli_path(&self) -> anyhow::Result<()> {
+        let cli_path = self
+            .resolve_cli_path(None)
+            .map_err(|status| anyhow::anyhow!("{}", status.message()))?;
+        self.validate_cli_path_is_executable(cli_path)
+            .map_err(|status| anyhow::anyhow!("{}", status.message()))
+    }
+
+    /// Acquires a permit against `--concurrency-limit`, when configured.
+    /// Returns `None` when no limit is set, so callers can hold the guard
+    /// for the lifetime of the RPC. Fails fast with `Status::resource_exhausted`
+    /// if `--acquire-timeout-ms` elapses before a permit frees up, or
+    /// immediately if `--max-queue-depth` waiters are already ahead of this
+    /// one, rather than queuing the caller forever or letting waiters pile
+    /// up with no bound on memory. If the semaphore itself closes while
+    /// waiting, reports `Status::unavailable` when `shutdown` has already
+    /// fired (the expected cause) and `Status::internal` otherwise, since an
+    /// unexpectedly closed semaphore outside of shutdown is a bug.
+    async fn acquire_permit(&self) -> Result<Option<OwnedSemaphorePermit>, Status> {
+        let Some(semaphore) = &self.concurrency_limit else {
+            return Ok(None);
+        };
+        let limit = self.concurrency_limit_value.unwrap_or_default();
+        if semaphore.available_permits() == 0 {
+            if let Some(max_queue_depth) = self.max_queue_depth {
+                let queue_depth = self.metrics.queue_depth();
+                if queue_depth as usize >= max_queue_depth {
+                    return Err(resource_exhausted_detail(format!(
+                        "queue is full (max_queue_depth={max_queue_depth}, limit={limit})"
+                    )));
+                }
+            }
+        }
+        self.metrics.record_queued();
+        let _queue_guard = QueueDepthGuard(&self.metrics);
+        let acquire = Arc::clone(semaphore).acquire_owned();
+        let permit = match self.acquire_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, acquire).await.map_err(|_| {
+                resource_exhausted_detail(format!(
+                    "no permit available within {timeout:?} (limit={limit}, queue_depth={})",
+                    self.metrics.queue_depth()
+                ))
+            })?,
+            None => acquire.await,
+        };
+        permit.map(Some).map_err(|err| {
+            if self.shutdown.is_cancelled() {
+                shutting_down_detail(format!("server is shutting down: {err}"))
+            } else {
+                internal_detail(format!("concurrency limit semaphore closed unexpectedly: {err}"))
+            }
+        })
+    }
+
+    /// Acquires a per-client slot against `--per-client-limit`, when
+    /// configured. `client_id` defaults to `""` when a request leaves it
+    /// unset, so all such requests share one bucket. Returns `None` when no
+    /// limit is set. Fails immediately with `Status::resource_exhausted` if
+    /// `client_id` already has `--per-client-limit` calls in flight, rather
+    /// than queuing — unlike [`Self::acquire_permit`], there's no
+    /// `--acquire-timeout-ms` equivalent for this limit.
+    fn acquire_per_client_permit(&self, client_id: Option<&str>) -> Result<Option<PerClientGuard>, Status> {
+        let Some(limit) = self.per_client_limit else {
+            return Ok(None);
+        };
+        let key = client_id.unwrap_or("").to_string();
+        let mut active = self
+            .active_per_client
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let count = active.entry(key.clone()).or_insert(0);
+        if *count >= limit {
+            return Err(resource_exhausted_detail(format!(
+                "client {key:?} already has {count} requests in flight (per_client_limit={limit})"
+            )));
+        }
+        *count += 1;
+        drop(active);
+        Ok(Some(PerClientGuard {
+            registry: Arc::clone(&self.active_per_client),
+            key,
+        }))
+    }
+
+    /// Waits for (or fails fast on) a `--spawn-rate` token before a new
+    /// command is spawned. No-op when no rate is configured. With
+    /// `--spawn-rate-reject`, a caller that finds the bucket empty is
+    /// rejected immediately with `Status::resource_exhausted` instead of
+    /// waiting for a token to refill; otherwise it waits, bounded by
+    /// `--spawn-rate-wait-ms` when set (unbounded otherwise, mirroring
+    /// [`Self::acquire_permit`]'s `--acquire-timeout-ms`-less wait). Loops
+    /// rather than sleeping once for the computed wait, since a concurrent
+    /// caller may claim a freshly-refilled token first.
+    async fn acquire_spawn_rate_token(&self) -> Result<(), Status> {
+        let Some(limiter) = &self.spawn_rate_limiter else {
+            return Ok(());
+        };
+        let started = Instant::now();
+        loop {
+            let wait = match limiter.try_acquire() {
+                Ok(()) => return Ok(()),
+                Err(wait) => wait,
+            };
+            if self.spawn_rate_reject {
+                return Err(resource_exhausted_detail(format!(
+                    "spawn rate limit exceeded; retry in {wait:?}"
+                )));
+            }
+            if let Some(bound) = self.spawn_rate_wait_bound {
+                if started.elapsed() + wait > bound {
+                    return Err(resource_exhausted_detail(format!(
+                        "no spawn rate token available within {bound:?}"
+                    )));
+                }
+            }
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Validates a request's `cwd`, when non-empty, before it's handed to
+    /// [`Command::current_dir`] so a bad path fails with a clear
+    /// `invalid_argument` instead of an opaque spawn error.
+    fn validate_cwd(&self, cwd: &str) -> Result<(), Status> {
+        if cwd.is_empty() {
+            return Ok(());
+        }
+        let path = std::path::Path::new(cwd);
+        if !path.is_absolute() {
+            return Err(invalid_argument_detail(format!(
+                "cwd must be an absolute path: {cwd}"
+            )));
+        }
+        let metadata = std::fs::metadata(path).map_err(|err| {
+            invalid_argument_detail(format!("cwd {cwd:?} does not exist: {err}"))
+        })?;
+        if !metadata.is_dir() {
+            return Err(invalid_argument_detail(format!(
+                "cwd {cwd:?} is not a directory"
+            )));
+        }
+        if let Some(root) = &self.allowed_cwd_root {
+            let canonical_cwd = std::fs::canonicalize(path).map_err(|err| {
+                invalid_argument_detail(format!("cwd {cwd:?} could not be canonicalized: {err}"))
+            })?;
+            let canonical_root = std::fs::canonicalize(root).map_err(|err| {
+                invalid_argument_detail(format!(
+                    "--allowed-cwd-root {root:?} could not be canonicalized: {err}"
+                ))
+            })?;
+            if !canonical_cwd.starts_with(&canonical_root) {
+                return Err(invalid_argument_detail(format!(
+                    "cwd {cwd:?} is outside the allowed root {root:?}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects an empty `args` when `--require-args` is set, so a malformed
+    /// client can't accidentally trigger the resolved CLI's bare-invocation
+    /// default behavior.
+    fn validate_args(&self, args: &[String]) -> Result<(), Status> {
+        if self.require_args && args.is_empty() {
+            return Err(invalid_argument_detail(
+                "args must not be empty (--require-args is set)",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Rejects a request whose first argument isn't in `--allowed-commands`,
+    /// when that flag was set. An empty allowlist (the default) means
+    /// allow-all, so servers that don't opt in keep running every subcommand.
+    fn validate_command_allowed(&self, args: &[String]) -> Result<(), Status> {
+        let Some(allowed_commands) = &self.allowed_commands else {
+            return Ok(());
+        };
+        if allowed_commands.is_empty() {
+            return Ok(());
+        }
+        match args.first() {
+            Some(command) if allowed_commands.contains(command) => Ok(()),
+            Some(command) => Err(permission_denied_detail(format!(
+                "command {command:?} is not in --allowed-commands"
+            ))),
+            None => Err(permission_denied_detail(
+                "args must start with an allowed command (--allowed-commands is set)",
+            )),
+        }
+    }
+
+    /// Validates a request's `stdout_file_path` against `--allowed-stdout-dir`
+    /// before it's opened, so a disallowed or malformed path fails with a
+    /// clear `invalid_argument` instead of leaking whether some other path
+    /// on disk exists.
+    fn validate_stdout_file_path(&self, path: &str) -> Result<PathBuf, Status> {
+        let Some(root) = &self.allowed_stdout_dir else {
+            return Err(permission_denied_detail(
+                "stdout_file_path requires the server to be started with --allowed-stdout-dir",
+            ));
+        };
+        let stdout_path = std::path::Path::new(path);
+        if !stdout_path.is_absolute() {
+            return Err(invalid_argument_detail(format!(
+                "stdout_file_path must be an absolute path: {path}"
+            )));
+        }
+        // `stdout_file_path` need not exist yet (it's about to be created),
+        // so canonicalize its parent directory rather than the full path,
+        // mirroring `validate_socket_path`'s handling of a not-yet-bound
+        // socket path in `lib.rs`.
+        let parent = stdout_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| std::path::Path::new("."));
+        let canonical_parent = std::fs::canonicalize(parent).map_err(|err| {
+            invalid_argument_detail(format!(
+                "stdout_file_path {path:?} parent directory could not be canonicalized: {err}"
+            ))
+        })?;
+        let canonical_root = std::fs::canonicalize(root).map_err(|err| {
+            invalid_argument_detail(format!(
+                "--allowed-stdout-dir {root:?} could not be canonicalized: {err}"
+            ))
+        })?;
+        if !canonical_parent.starts_with(&canonical_root) {
+            return Err(invalid_argument_detail(format!(
+                "stdout_file_path {path:?} is outside the allowed root {root:?}"
+            )));
+        }
+        let Some(file_name) = stdout_path.file_name() else {
+            return Err(invalid_argument_detail(format!(
+                "stdout_file_path {path:?} has no file name"
+            )));
+        };
+        Ok(canonical_parent.join(file_name))
+    }
+
+    /// Validates a request's `stdin_file_path` against `--allowed-stdin-dir`
+    /// before it's read, mirroring [`Self::validate_stdout_file_path`].
+    fn validate_stdin_file_path(&self, path: &str) -> Result<PathBuf, Status> {
+        let Some(root) = &self.allowed_stdin_dir else {
+            return Err(permission_denied_detail(
+                "stdin_file_path requires the server to be started with --allowed-stdin-dir",
+            ));
+        };
+        let stdin_path = std::path::Path::new(path);
+        if !stdin_path.is_absolute() {
+            return Err(invalid_argument_detail(format!(
+                "stdin_file_path must be an absolute path: {path}"
+            )));
+        }
+        // Unlike `stdout_file_path`, `stdin_file_path` is read rather than
+        // created, so it must already exist and can be canonicalized in
+        // full, resolving any `..` components or symlinks before the
+        // confinement check below.
+        let canonical_stdin = std::fs::canonicalize(stdin_path).map_err(|err| {
+            invalid_argument_detail(format!(
+                "stdin_file_path {path:?} could not be canonicalized: {err}"
+            ))
+        })?;
+        let canonical_root = std::fs::canonicalize(root).map_err(|err| {
+            invalid_argument_detail(format!(
+                "--allowed-stdin-dir {root:?} could not be canonicalized: {err}"
+            ))
+        })?;
+        if !canonical_stdin.starts_with(&canonical_root) {
+            return Err(invalid_argument_detail(format!(
+                "stdin_file_path {path:?} is outside the allowed root {root:?}"
+            )));
+        }
+        Ok(canonical_stdin)
+    }
+
+    /// Rejects `stdin` once it exceeds `--max-stdin-bytes`, giving a clearer
+    /// error than the generic `max_request_bytes` decode failure when stdin
+    /// is the dominant part of an oversized message. Unset means unbounded.
+    fn validate_stdin_size(&self, stdin: &[u8]) -> Result<(), Status> {
+        let Some(max_stdin_bytes) = self.max_stdin_bytes else {
+            return Ok(());
+        };
+        if stdin.len() > max_stdin_bytes {
+            return Err(invalid_argument_detail(format!(
+                "stdin is {} bytes, exceeding --max-stdin-bytes={max_stdin_bytes}",
+                stdin.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Validates a request's `nice`, when set, against the standard
+    /// `setpriority` range and `--allow-nice`. Returns the value to apply,
+    /// or `None` when `nice` was unset.
+    fn validate_nice(&self, nice: Option<i32>) -> Result<Option<i32>, Status> {
+        let Some(nice) = nice else {
+            return Ok(None);
+        };
+        if !self.allow_nice {
+            return Err(permission_denied_detail(
+                "nice requires the server to be started with --allow-nice",
+            ));
+        }
+        if !(-20..=19).contains(&nice) {
+            return Err(invalid_argument_detail(format!(
+                "nice must be between -20 and 19, got {nice}"
+            )));
+        }
+        Ok(Some(nice))
+    }
+
+    /// Validates a request's `umask`, when set, against the standard
+    /// 0..=0o777 octal range and `--allow-umask`. Returns the value to
+    /// apply, or `None` when `umask` was unset.
+    fn validate_umask(&self, umask: Option<u32>) -> Result<Option<u32>, Status> {
+        let Some(umask) = umask else {
+            return Ok(None);
+        };
+        if !self.allow_umask {
+            return Err(permission_denied_detail(
+                "umask requires the server to be started with --allow-umask",
+            ));
+        }
+        if umask > 0o777 {
+            return Err(invalid_argument_detail(format!(
+                "umask must be between 0 and 0o777, got {umask:#o}"
+            )));
+        }
+        Ok(Some(umask))
+    }
+
+    /// Validates a request's `extra_fds` against `--allow-extra-fds`, the
+    /// reserved standard-stream fds, and duplicates. `fd` numbers 0-2 are
+    /// rejected since they'd collide with the child's own stdin/stdout/stderr
+    /// setup rather than opening a genuinely extra descriptor.
+    fn validate_extra_fds(&self, extra_fds: &[u32]) -> Result<(), Status> {
+        if extra_fds.is_empty() {
+            return Ok(());
+        }
+        if !self.allow_extra_fds {
+            return Err(permission_denied_detail(
+                "extra_fds requires the server to be started with --allow-extra-fds",
+            ));
+        }
+        let mut seen = HashSet::new();
+        for &fd in extra_fds {
+            if fd <= 2 {
+                return Err(invalid_argument_detail(format!(
+                    "extra_fds must be 3 or greater, got {fd}"
+                )));
+            }
+            if !seen.insert(fd) {
+                return Err(invalid_argument_detail(format!(
+                    "extra_fds contains duplicate fd {fd}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates a request's `cpu_affinity` against `--allow-cpu-affinity`
+    /// and the host's online core count. Linux-only; rejected outright on
+    /// other platforms regardless of `--allow-cpu-affinity`, since
+    /// [`crate::process_group::apply_cpu_affinity`] is a no-op there.
+    fn validate_cpu_affinity(&self, cpu_affinity: &[u32]) -> Result<(), Status> {
+        if cpu_affinity.is_empty() {
+            return Ok(());
+        }
+        if !self.allow_cpu_affinity {
+            return Err(permission_denied_detail(
+                "cpu_affinity requires the server to be started with --allow-cpu-affinity",
+            ));
+        }
+        #[cfg(target_os = "linux")]
+        {
+            let available = crate::process_group::available_cpu_count();
+            for &core in cpu_affinity {
+                if core as usize >= available {
+                    return Err(invalid_argument_detail(format!(
+                        "cpu_affinity core {core} is outside the available range (host reports {available} online)"
+                    )));
+                }
+            }
+            Ok(())
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Err(permission_denied_detail(
+                "cpu_affinity is only supported on Linux",
+            ))
+        }
+    }
+
+    /// Spawns `command`, retrying with exponential backoff up to
+    /// `--spawn-retries` times when `spawn` fails with `EAGAIN` (fork
+    /// temporarily unavailable) or `ENOMEM` (no memory to fork), since both
+    /// are transient symptoms of a loaded host rather than misconfiguration.
+    /// Any other error (e.g. `ENOENT`, `EACCES`) fails immediately.
+    async fn spawn_with_retry(&self, command: &mut Command) -> std::io::Result<tokio::process::Child> {
+        let mut attempt = 0;
+        loop {
+            match command.spawn() {
+                Ok(child) => return Ok(child),
+                Err(err) if attempt < self.spawn_retries && is_transient_spawn_error(&err) => {
+                    let delay = Duration::from_millis(100 << attempt.min(10));
+                    tracing::warn!(
+                        error = %err,
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        "transient spawn failure; retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Validates every key in `env` is non-empty and contains neither `=`
+    /// nor a NUL byte, either of which makes `Command::envs`' behavior
+    /// platform-dependent and confusing for the spawned child. With
+    /// `--reject-malformed-env`, fails the whole request with
+    /// `invalid_argument`; otherwise drops the malformed entries from the
+    /// returned map (their names are returned too, for the caller to log)
+    /// and keeps the rest.
+    fn validate_env_keys(
+        &self,
+        env: &std::collections::HashMap<String, String>,
+    ) -> Result<(std::collections::HashMap<String, String>, Vec<String>), Status> {
+        let mut valid = std::collections::HashMap::new();
+        let mut malformed = Vec::new();
+        for (name, value) in env {
+            if name.is_empty() || name.contains('=') || name.contains('\0') {
+                malformed.push(name.clone());
+            } else {
+                valid.insert(name.clone(), value.clone());
+            }
+        }
+        if self.reject_malformed_env && !malformed.is_empty() {
+            return Err(invalid_argument_detail(format!(
+                "malformed environment variable names: {}",
+                malformed.join(", ")
+            )));
+        }
+        Ok((valid, malformed))
+    }
+
+    /// Splits `env` into variables allowed by `--env-allowlist` and the
+    /// names of those that were rejected. Returns every variable when no
+    /// allowlist is configured, preserving the previous passthrough behavior.
+    fn filter_env<'a>(
+        &self,
+        env: &'a std::collections::HashMap<String, String>,
+    ) -> (Vec<(&'a str, &'a str)>, Vec<&'a str>) {
+        let Some(allowlist) = &self.env_allowlist else {
+            return (
+                env.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect(),
+                Vec::new(),
+            );
+        };
+
+        let mut allowed = Vec::new();
+        let mut rejected = Vec::new();
+        for (name, value) in env {
+            if allowlist.contains(name) {
+                allowed.push((name.as_str(), value.as_str()));
+            } else {
+                rejected.push(name.as_str());
+            }
+        }
+        (allowed, rejected)
+    }
+
+    fn command_for(&self, cli_path: &std::path::Path, args: &[String], cwd: &str) -> Command {
+        let mut command = Command::new(cli_path);
+        command.args(args);
+        if !cwd.is_empty() {
+            command.current_dir(cwd);
+        } else if let Some(default_cwd) = &self.default_cwd {
+            command.current_dir(default_cwd);
+        }
+        command.stdin(std::process::Stdio::piped());
+        command.stdout(std::process::Stdio::piped());
+        command.stderr(std::process::Stdio::piped());
+        set_own_process_group(&mut command);
+        command
+    }
+}
+
+/// Kills `child` and, on Unix, every other process in its process group so
+/// that grandchildren it forked don't outlive it.
+fn kill_child_process_group(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        if let Some(pid) = child.id() {
+            crate::process_group::kill_process_group(pid, libc::SIGKILL);
+            return;
+        }
+    }
+    let _ = child.start_kill();
+}
+
+/// Kills `child` for exceeding a timeout and waits for it to exit, escalating
+/// from `SIGTERM` to `SIGKILL` when `term_grace` is set: sends `SIGTERM` to
+/// the process group first and waits up to `term_grace` for the child to
+/// exit on its own, giving a well-behaved CLI a chance to flush logs and
+/// clean up temp files, before falling back to the immediate
+/// [`kill_child_process_group`] `SIGKILL` that `term_grace` being unset sends
+/// right away. Unix-only; `SIGTERM` has nothing to escalate from elsewhere,
+/// so non-Unix platforms always kill immediately regardless of `term_grace`.
+#[cfg(unix)]
+async fn kill_child_with_grace(
+    child: &mut Child,
+    term_grace: Option<Duration>,
+) -> std::io::Result<std::process::ExitStatus> {
+    if let Some(term_grace) = term_grace
+        && let Some(pid) = child.id()
+    {
+        crate::process_group::kill_process_group(pid, libc::SIGTERM);
+        if let Ok(status) = tokio::time::timeout(term_grace, child.wait()).await {
+            return status;
+        }
+    }
+    kill_child_process_group(child);
+    child.wait().await
+}
+
+#[cfg(not(unix))]
+async fn kill_child_with_grace(
+    child: &mut Child,
+    _term_grace: Option<Duration>,
+) -> std::io::Result<std::process::ExitStatus> {
+    kill_child_process_group(child);
+    child.wait().await
+}
+
+/// Wraps a spawned [`Child`], killing its process group when dropped while
+/// still running. A normal `.wait()` leaves `child.id()` as `None`, so the
+/// kill on drop is a no-op on the happy path; it only does real work when
+/// this guard is dropped mid-RPC because the caller disconnected and tonic
+/// dropped the in-flight `run_command` future before it reached `.wait()`.
+/// This is what makes `run_command` abandon-on-disconnect: no explicit
+/// cancellation signal is threaded through, the future being dropped early
+/// *is* the disconnect signal.
+struct ChildGuard(Child);
+
+impl std::ops::Deref for ChildGuard {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for ChildGuard {
+    fn deref_mut(&mut self) -> &mut Child {
+        &mut self.0
+    }
+}
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        kill_child_process_group(&mut self.0);
+    }
+}
+
+/// Kills the process group led by `pid`. Used by `cancel_command`, which
+/// only has a bare pid on hand (not a [`Child`]) since the owning
+/// `run_command`/`run_interactive_command` call is running on a different
+/// task. A no-op on non-Unix platforms, same as [`kill_child_process_group`].
+fn kill_pid(pid: u32) {
+    #[cfg(unix)]
+    {
+        crate::process_group::kill_process_group(pid, libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+    }
+}
+
+/// Registers `pid` under `request_id` in `registry` for the lifetime of the
+/// guard, so `cancel_command` can find and kill it, removing the entry again
+/// on drop. A no-op when `request_id` is unset, since there's then nothing
+/// for `cancel_command` to look up.
+struct ActiveRequestGuard {
+    registry: Arc<Mutex<HashMap<String, u32>>>,
+    request_id: Option<String>,
+}
+
+impl ActiveRequestGuard {
+    fn new(registry: Arc<Mutex<HashMap<String, u32>>>, request_id: Option<String>, pid: u32) -> Self {
+        if let Some(request_id) = &request_id {
+            registry
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .insert(request_id.clone(), pid);
+        }
+        Self { registry, request_id }
+    }
+}
+
+impl Drop for ActiveRequestGuard {
+    fn drop(&mut self) {
+        if let Some(request_id) = &self.request_id {
+            self.registry
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .remove(request_id);
+        }
+    }
+}
+
+/// An in-flight `run_command`/`run_interactive_command` invocation tracked
+/// for `list_running`; see [`CodexCliService::running_commands`].
+struct RunningCommandEntry {
+    request_id: Option<String>,
+    args: Vec<String>,
+    started_at: Instant,
+}
+
+/// Unconditionally registers `pid` in `registry` for `list_running` for the
+/// lifetime of the guard, removing the entry again on drop. Unlike
+/// [`ActiveRequestGuard`], tracks every invocation regardless of
+/// `request_id`, since `list_running` is meant to show everything currently
+/// running, not just the ones `cancel_command` can look up.
+struct RunningCommandGuard {
+    registry: Arc<Mutex<HashMap<u32, RunningCommandEntry>>>,
+    pid: u32,
+}
+
+impl RunningCommandGuard {
+    fn new(
+        registry: Arc<Mutex<HashMap<u32, RunningCommandEntry>>>,
+        pid: u32,
+        request_id: Option<String>,
+        args: Vec<String>,
+    ) -> Self {
+        registry.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).insert(
+            pid,
+            RunningCommandEntry { request_id, args, started_at: Instant::now() },
+        );
+        Self { registry, pid }
+    }
+}
+
+impl Drop for RunningCommandGuard {
+    fn drop(&mut self) {
+        self.registry
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&self.pid);
+    }
+}
+
+/// Decrements a client's in-flight count in `active_per_client` when its
+/// `RunCommand`/`RunInteractiveCommand` call finishes, for `--per-client-limit`.
+/// Holds no permit of its own; [`CodexCliService::acquire_per_client_permit`]
+/// increments the count before returning one.
+struct PerClientGuard {
+    registry: Arc<Mutex<HashMap<String, usize>>>,
+    key: String,
+}
+
+impl Drop for PerClientGuard {
+    fn drop(&mut self) {
+        let mut active = self.registry.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(count) = active.get_mut(&self.key) {
+            *count -= 1;
+            if *count == 0 {
+                active.remove(&self.key);
+            }
+        }
+    }
+}
+
+/// Decrements `Metrics::queue_depth` when a request stops waiting on a
+/// concurrency-limit permit, however it stops waiting: permit acquired,
+/// `--acquire-timeout` elapsed, or the call dropped outright.
+struct QueueDepthGuard<'a>(&'a crate::metrics::Metrics);
+
+impl Drop for QueueDepthGuard<'_> {
+    fn drop(&mut self) {
+        self.0.record_dequeued();
+    }
+}
+
+/// Token-bucket backing `--spawn-rate`. Refills lazily from elapsed
+/// wall-clock time on each acquire attempt rather than via a background
+/// task, so an idle bridge costs nothing between spawns. Capacity equals
+/// the configured rate, allowing up to one second of burst above the
+/// steady-state rate.
+struct SpawnRateLimiter {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<SpawnRateLimiterState>,
+}
+
+struct SpawnRateLimiterState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl SpawnRateLimiter {
+    fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            capacity: rate,
+            state: Mutex::new(SpawnRateLimiterState { tokens: rate, last_refill: Instant::now() }),
+        }
+    }
+
+    /// Refills for elapsed time and takes one token if one is available.
+    /// Otherwise returns how long the caller would need to wait before a
+    /// token would be available, assuming no one else claims it first.
+    fn try_acquire(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.rate).min(self.capacity);
+        state.last_refill = now;
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - state.tokens) / self.rate))
+        }
+    }
+}
+
+/// Bytes captured from a child's stdout/stderr so far, shared between the
+/// reading future and whatever abandons it mid-read (namely the timeout
+/// branch of `await_with_deadline!`, which drops the reading future outright
+/// once it fires). Keeping the buffer outside the dropped future is what lets
+/// a killed command's response still carry the output it produced before
+/// being killed.
+#[derive(Default)]
+struct CapturedOutput {
+    bytes: Vec<u8>,
+    truncated: bool,
+    /// When set, `bytes` is kept as a fixed-capacity ring buffer holding only
+    /// the most recently read `tail_capacity` bytes — the oldest bytes are
+    /// dropped as new ones arrive — instead of the usual behavior of
+    /// capturing up to some limit and discarding anything past that. Set
+    /// from a request's `tail_bytes`; see [`tail_capacity_hint`].
+    tail_capacity: Option<usize>,
+}
+
+impl CapturedOutput {
+    /// Pre-sizes the buffer to `capacity` bytes, so `read_stream` doesn't
+    /// have to grow (and reallocate) it incrementally as bytes arrive. See
+    /// [`capture_capacity_hint`] for how `capacity` is derived from a
+    /// request's `expected_output_bytes`.
+    fn with_capacity(capacity: usize) -> Self {
+        Self { bytes: Vec::with_capacity(capacity), truncated: false, tail_capacity: None }
+    }
+
+    /// Like [`Self::with_capacity`], but `capacity` is the fixed size of a
+    /// ring buffer that keeps only the most recently read bytes rather than
+    /// the first ones. See [`tail_capacity_hint`].
+    fn with_tail_capacity(capacity: usize) -> Self {
+        Self { bytes: Vec::with_capacity(capacity), truncated: false, tail_capacity: Some(capacity) }
+    }
+
+    /// Takes the captured bytes and truncated flag, leaving an empty buffer
+    /// behind. Used once a read finishes (normally or by being abandoned) and
+    /// the caller is ready to build a response from what was captured.
+    fn take(&mut self) -> (Vec<u8>, bool) {
+        (std::mem::take(&mut self.bytes), self.truncated)
+    }
+}
+
+/// Clamps a request's `expected_output_bytes` hint against `--max-output-bytes`
+/// (when set), for passing to [`CapturedOutput::with_capacity`]. Purely a
+/// performance hint; an inaccurate guess doesn't affect correctness, but an
+/// unclamped one could force a speculative allocation far larger than the
+/// response could ever actually hold.
+fn capture_capacity_hint(expected_output_bytes: Option<u64>, max_bytes: Option<usize>) -> usize {
+    let hint = expected_output_bytes.unwrap_or(0) as usize;
+    match max_bytes {
+        Some(max_bytes) => hint.min(max_bytes),
+        None => hint,
+    }
+}
+
+/// Derives the ring buffer capacity for [`CapturedOutput::with_tail_capacity`]
+/// from a request's `tail_bytes`, clamped against `--max-output-bytes` (when
+/// set) the same way [`capture_capacity_hint`] clamps `expected_output_bytes`.
+/// Returns `None` when `tail_bytes` is unset, meaning tail-only capture is
+/// off and the ordinary head-truncating behavior applies instead.
+fn tail_capacity_hint(tail_bytes: Option<u64>, max_bytes: Option<usize>) -> Option<usize> {
+    let tail_bytes = tail_bytes? as usize;
+    Some(match max_bytes {
+        Some(max_bytes) => tail_bytes.min(max_bytes),
+        None => tail_bytes,
+    })
+}
+
+/// Builds the [`CapturedOutput`] a stream should be read into for a request,
+/// in tail-only ring buffer mode when `tail_bytes` is set and the ordinary
+/// head-truncating mode otherwise.
+fn new_captured_output(
+    expected_output_bytes: Option<u64>,
+    tail_bytes: Option<u64>,
+    max_bytes: Option<usize>,
+) -> CapturedOutput {
+    match tail_capacity_hint(tail_bytes, max_bytes) {
+        Some(capacity) => CapturedOutput::with_tail_capacity(capacity),
+        None => CapturedOutput::with_capacity(capture_capacity_hint(expected_output_bytes, max_bytes)),
+    }
+}
+
+/// Reads `reader` to completion, stopping early once `max_bytes` bytes have
+/// been captured so a runaway process can't OOM the server, or, when
+/// `captured` was built with [`CapturedOutput::with_tail_capacity`], keeping
+/// only the most recently read bytes instead. Appends into `captured` as
+/// data arrives, rather than returning a buffer only at the end, so a caller
+/// that has to abandon this future mid-read can still recover whatever was
+/// read so far from `captured`.
+async fn read_stream(
+    mut reader: impl AsyncRead + Unpin,
+    max_bytes: Option<usize>,
+    captured: &Mutex<CapturedOutput>,
+) -> std::io::Result<()> {
+    let mut chunk = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        let mut captured = captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(tail_capacity) = captured.tail_capacity {
+            captured.bytes.extend_from_slice(&chunk[..n]);
+            if captured.bytes.len() > tail_capacity {
+                let excess = captured.bytes.len() - tail_capacity;
+                captured.bytes.drain(..excess);
+                captured.truncated = true;
+            }
+            continue;
+        }
+        if captured.truncated {
+            // Keep draining the pipe so the child doesn't block on a full
+            // one, but stop buffering bytes we'll never return.
+            continue;
+        }
+        match max_bytes {
+            Some(max_bytes) => {
+                let remaining = max_bytes - captured.bytes.len();
+                if n > remaining {
+                    captured.truncated = true;
+                }
+                captured.bytes.extend_from_slice(&chunk[..n.min(remaining)]);
+            }
+            None => captured.bytes.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+/// Reads every `extra_fds` pipe concurrently into its own `CapturedOutput`,
+/// the same way [`read_stream`] does for stdout/stderr, so a request's
+/// `extra_fds` get the same truncation and "return whatever was captured
+/// before being killed" behavior.
+async fn read_extra_fds(
+    pipes: Vec<(u32, tokio::fs::File)>,
+    max_bytes: Option<usize>,
+    captured: &[(u32, Mutex<CapturedOutput>)],
+) -> std::io::Result<()> {
+    let reads = pipes.into_iter().map(|(fd, pipe)| {
+        let slot = captured
+            .iter()
+            .find(|(captured_fd, _)| *captured_fd == fd)
+            .map(|(_, captured)| captured)
+            .expect("a captured slot exists for every extra fd pipe");
+        read_stream(pipe, max_bytes, slot)
+    });
+    futures::future::try_join_all(reads).await?;
+    Ok(())
+}
+
+/// Builds the `extra_fd_outputs` response field, in the same order as the
+/// request's `extra_fds`, from whatever `read_extra_fds` captured for each.
+fn extra_fd_outputs(
+    req_extra_fds: &[u32],
+    captured: &[(u32, Mutex<CapturedOutput>)],
+) -> Vec<crate::proto::ExtraFdOutput> {
+    req_extra_fds
+        .iter()
+        .map(|&fd| {
+            let (data, truncated) = captured
+                .iter()
+                .find(|(captured_fd, _)| *captured_fd == fd)
+                .map(|(_, captured)| {
+                    captured
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .take()
+                })
+                .unwrap_or_default();
+            crate::proto::ExtraFdOutput { fd, data, truncated }
+        })
+        .collect()
+}
+
+/// Writes `data` to `stdin` and closes it by dropping it, run concurrently
+/// with the output readers in the same `try_join!` so a child that starts
+/// producing output before it's done reading stdin can't deadlock against a
+/// full pipe buffer on either side. A child that exits before consuming all
+/// of `data` closes its end of the pipe, which surfaces here as a
+/// `BrokenPipe` write error; that's normal process behavior (the same thing
+/// that happens piping into `head`), not a failure of the RPC, so it's
+/// swallowed and recorded in `truncated` instead of propagated.
+async fn write_stdin(mut stdin: tokio::process::ChildStdin, data: Vec<u8>, truncated: &Mutex<bool>) -> std::io::Result<()> {
+    if !data.is_empty() {
+        if let Err(err) = stdin.write_all(&data).await {
+            if err.kind() == std::io::ErrorKind::BrokenPipe {
+                *truncated.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = true;
+                drop(stdin);
+                return Ok(());
+            }
+            return Err(err);
+        }
+    }
+    drop(stdin);
+    Ok(())
+}
+
+/// Forwards `start`'s initial `stdin` payload followed by `StdinChunk`s from
+/// an interactive client stream to the child's stdin, run concurrently with
+/// the stdout/stderr readers in the same `try_join!` for the same reason
+/// [`write_stdin`] is: a child that starts producing output before all of
+/// `initial_stdin` is written, or before the client sends `CloseStdin`,
+/// would otherwise block on a full pipe while this function sits there
+/// still writing or waiting on the next client message, deadlocking the
+/// RPC. Stops forwarding (and closes stdin by dropping it) on `CloseStdin`
+/// or end of stream. Validation failures that should fail the RPC (an
+/// oversized chunk, a second `start`, or a transport error on the stream
+/// itself) are recorded into `stream_error` instead of being returned
+/// directly, since every future in the same `try_join!` must share an
+/// `io::Result` error type.
+async fn forward_interactive_stdin(
+    stream: &mut Streaming<InteractiveCommandInput>,
+    mut stdin: tokio::process::ChildStdin,
+    initial_stdin: Vec<u8>,
+    max_request_bytes: usize,
+    stream_error: &Mutex<Option<Status>>,
+) -> std::io::Result<()> {
+    if !initial_stdin.is_empty() {
+        stdin.write_all(&initial_stdin).await?;
+    }
+    loop {
+        let message = match stream.message().await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(status) => {
+                *stream_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(status);
+                break;
+            }
+        };
+        match message.input {
+            Some(Input::StdinChunk(chunk)) => {
+                if chunk.len() > max_request_bytes {
+                    *stream_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) =
+                        Some(invalid_argument_detail(format!(
+                            "stdin_chunk of {} bytes exceeds --max-request-bytes ({max_request_bytes})",
+                            chunk.len(),
+                        )));
+                    break;
+                }
+                stdin.write_all(&chunk).await?;
+            }
+            Some(Input::CloseStdin(_)) => break,
+            Some(Input::Start(_)) => {
+                *stream_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) =
+                    Some(invalid_argument_detail("`start` may only be sent once"));
+                break;
+            }
+            None => {}
+        }
+    }
+    drop(stdin);
+    Ok(())
+}
+
+/// Reads `stdout` and `stderr` concurrently into a single buffer in the
+/// order bytes actually arrive, for callers that set `merge_streams` and
+/// want output that reads the way it would have in a terminal. Stops
+/// buffering once `max_bytes` is reached (or keeps only the tail, same as
+/// [`read_stream`]) but keeps draining both pipes so the child doesn't
+/// block. Appends into `captured` as data arrives for the same reason
+/// `read_stream` does.
+async fn read_streams_merged(
+    mut stdout: impl AsyncRead + Unpin,
+    mut stderr: impl AsyncRead + Unpin,
+    max_bytes: Option<usize>,
+    captured: &Mutex<CapturedOutput>,
+) -> std::io::Result<()> {
+    let mut stdout_chunk = [0u8; 8192];
+    let mut stderr_chunk = [0u8; 8192];
+    let mut stdout_open = true;
+    let mut stderr_open = true;
+
+    while stdout_open || stderr_open {
+        let (n, chunk): (usize, &[u8]) = tokio::select! {
+            result = stdout.read(&mut stdout_chunk), if stdout_open => {
+                let n = result?;
+                if n == 0 {
+                    stdout_open = false;
+                }
+                (n, &stdout_chunk[..n])
+            }
+            result = stderr.read(&mut stderr_chunk), if stderr_open => {
+                let n = result?;
+                if n == 0 {
+                    stderr_open = false;
+                }
+                (n, &stderr_chunk[..n])
+            }
+        };
+        if n == 0 {
+            continue;
+        }
+        let mut captured = captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(tail_capacity) = captured.tail_capacity {
+            captured.bytes.extend_from_slice(chunk);
+            if captured.bytes.len() > tail_capacity {
+                let excess = captured.bytes.len() - tail_capacity;
+                captured.bytes.drain(..excess);
+                captured.truncated = true;
+            }
+            continue;
+        }
+        match max_bytes {
+            Some(max_bytes) if !captured.truncated => {
+                let remaining = max_bytes - captured.bytes.len();
+                if n > remaining {
+                    captured.truncated = true;
+                }
+                captured.bytes.extend_from_slice(&chunk[..n.min(remaining)]);
+            }
+            Some(_) => {}
+            None => captured.bytes.extend_from_slice(chunk),
+        }
+    }
+    Ok(())
+}
+
+/// Extracts a W3C `traceparent`/`tracestate` pair from incoming gRPC
+/// metadata, if the caller sent one, so [`CodexCliService::run_command`]'s
+/// span can be linked into the caller's trace instead of starting a new one.
+fn trace_headers_from_metadata(
+    metadata: &tonic::metadata::MetadataMap,
+) -> (Option<String>, Option<String>) {
+    let traceparent = metadata
+        .get("traceparent")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let tracestate = metadata
+        .get("tracestate")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    (traceparent, tracestate)
+}
+
+/// Parses the gRPC `grpc-timeout` request header (e.g. `"5000m"` for 5000
+/// milliseconds) into a [`Duration`], per the HTTP/2 transport spec. Returns
+/// `None` if the header is absent or malformed, so a cancelled or
+/// deadline-bound call can still be bounded server-side even when the client
+/// didn't also set `timeout_ms` on the request body.
+fn grpc_timeout_from_metadata(metadata: &tonic::metadata::MetadataMap) -> Option<Duration> {
+    let value = metadata.get("grpc-timeout")?.to_str().ok()?;
+    let split_at = value.len().checked_sub(1)?;
+    let (digits, unit) = value.split_at(split_at);
+    let amount: u64 = digits.parse().ok()?;
+    match unit {
+        "H" => Some(Duration::from_secs(amount.saturating_mul(3600))),
+        "M" => Some(Duration::from_secs(amount.saturating_mul(60))),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// Returns the signal that terminated `status`, if any. Always `None` when
+/// the process exited normally or on non-Unix platforms.
+fn terminating_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        return status.signal();
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        None
+    }
+}
+
+impl CodexCliService {
+    /// Does the actual work of `run_command`, split out so the trait method
+    /// can wrap it uniformly with RED metrics regardless of which of the
+    /// several early-return error paths below is taken.
+    /// Runs a single attempt of `run_command`. Takes the request body and
+    /// metadata separately, rather than a `Request<RunCommandRequest>`, so
+    /// [`Self::run_command`] can call this once per `retry_on_exit_codes`
+    /// attempt with a fresh `req` while reusing the same gRPC metadata
+    /// (deadline, trace headers) across every attempt.
+    async fn run_command_impl(
+        &self,
+        req: RunCommandRequest,
+        metadata: &tonic::metadata::MetadataMap,
+        started_at: std::time::Instant,
+    ) -> Result<Response<RunCommandResponse>, Status> {
+        tracing::info!("run_command started");
+        self.acquire_spawn_rate_token().await?;
+        let _permit = self.acquire_permit().await?;
+        let queue_wait_ms = started_at.elapsed().as_millis() as u64;
+
+        let grpc_deadline = grpc_timeout_from_metadata(metadata);
+        let (traceparent, tracestate) = trace_headers_from_metadata(metadata);
+        if let Some(context) = traceparent.as_deref().and_then(|traceparent| {
+            codex_otel::context_from_trace_headers(Some(traceparent), tracestate.as_deref())
+        }) {
+            codex_otel::set_parent_from_context(&tracing::Span::current(), context);
+        }
+        self.metrics.record_tag(req.tag.as_deref());
+        let _per_client_permit = self.acquire_per_client_permit(req.client_id.as_deref())?;
+        self.validate_cwd(&req.cwd)?;
+        self.validate_args(&req.args)?;
+        self.validate_command_allowed(&req.args)?;
+        self.validate_stdin_size(&req.stdin)?;
+        let (valid_env, malformed_env) = self.validate_env_keys(&req.env)?;
+        for name in &malformed_env {
+            tracing::warn!(env_var = name, "dropped malformed environment variable name");
+        }
+        let (allowed_env, rejected_env) = self.filter_env(&valid_env);
+        for name in &rejected_env {
+            tracing::warn!(env_var = name, "rejected disallowed environment variable");
+        }
+        if self.reject_disallowed_env && !rejected_env.is_empty() {
+            return Err(invalid_argument_detail(format!(
+                "environment variables not in --env-allowlist: {}",
+                rejected_env.join(", ")
+            )));
+        }
+
+        let cli_path = self.resolve_cli_path(req.cli_path.as_deref())?;
+        self.validate_cli_path_is_executable(cli_path)?;
+
+        let stdout_redirect = match req.stdout_file_path.as_deref() {
+            Some(_) if req.merge_streams => {
+                return Err(invalid_argument_detail(
+                    "stdout_file_path and merge_streams are mutually exclusive",
+                ));
+            }
+            Some(path) => Some(self.validate_stdout_file_path(path)?),
+            None => None,
+        };
+
+        let stdin_bytes = match req.stdin_file_path.as_deref() {
+            Some(_) if !req.stdin.is_empty() => {
+                return Err(invalid_argument_detail(
+                    "stdin_file_path and stdin are mutually exclusive",
+                ));
+            }
+            Some(path) => {
+                let stdin_path = self.validate_stdin_file_path(path)?;
+                std::fs::read(&stdin_path)
+                    .map_err(|err| internal_detail(format!("failed to read stdin_file_path: {err}")))?
+            }
+            None => req.stdin.clone(),
+        };
+
+        let resource_limits = crate::process_group::ResourceLimits {
+            max_cpu_seconds: req.max_cpu_seconds,
+            max_memory_bytes: req.max_memory_bytes,
+            max_open_files: req.max_open_files,
+        };
+        if !resource_limits.is_empty() && !self.allow_rlimits {
+            return Err(permission_denied_detail(
+                "max_cpu_seconds/max_memory_bytes/max_open_files require the server to be started with --allow-rlimits",
+            ));
+        }
+        let nice = self.validate_nice(req.nice)?;
+        let umask = self.validate_umask(req.umask)?;
+        self.validate_extra_fds(&req.extra_fds)?;
+        self.validate_cpu_affinity(&req.cpu_affinity)?;
+
+        if req.dry_run {
+            tracing::info!("run_command dry run; not spawning anything");
+            return Ok(Response::new(RunCommandResponse {
+                dry_run: Some(crate::proto::DryRunPlan {
+                    resolved_cli_path: cli_path.display().to_string(),
+                    args: req.args.clone(),
+                    cwd: req.cwd.clone(),
+                    effective_env: self
+                        .default_env
+                        .iter()
+                        .map(|(name, value)| (name.clone(), value.clone()))
+                        .chain(
+                            allowed_env
+                                .iter()
+                                .map(|(name, value)| (name.to_string(), value.to_string())),
+                        )
+                        .collect(),
+                    rejected_env_vars: rejected_env.iter().map(|name| name.to_string()).collect(),
+                }),
+                tag: req.tag.clone(),
+                ..Default::default()
+            }));
+        }
+
+        let mut command = self.command_for(cli_path, &req.args, &req.cwd);
+        if req.clear_env {
+            command.env_clear();
+        } else if req.inherit_server_env {
+            command.envs(std::env::vars());
+        }
+        command.envs(&self.default_env);
+        command.envs(allowed_env);
+        self.strip_proxy_env(&mut command);
+        if let Some(traceparent) = &traceparent {
+            command.env("TRACEPARENT", traceparent);
+            if let Some(tracestate) = &tracestate {
+                command.env("TRACESTATE", tracestate);
+            }
+        }
+        if let Some(stdout_path) = &stdout_redirect {
+            let file = std::fs::File::create(stdout_path).map_err(|err| {
+                internal_detail(format!("failed to open stdout_file_path: {err}"))
+            })?;
+            command.stdout(std::process::Stdio::from(file));
+        }
+        crate::process_group::apply_rlimits(&mut command, resource_limits);
+        if let Some(nice) = nice {
+            crate::process_group::apply_nice(&mut command, nice);
+        }
+        if let Some(umask) = umask {
+            crate::process_group::apply_umask(&mut command, umask);
+        }
+        crate::process_group::apply_cpu_affinity(&mut command, req.cpu_affinity.clone());
+        let (extra_fd_pipes, extra_fd_write_fds) = if req.extra_fds.is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            crate::process_group::open_extra_fd_pipes(&mut command, &req.extra_fds)
+                .map_err(|err| spawn_failed_detail(format!("failed to create extra_fds pipes: {err}")))?
+        };
+        let started_at_millis = unix_millis_now();
+        let rusage_before = crate::process_group::children_resource_usage();
+        let spawn_started_at = std::time::Instant::now();
+        let spawn_result = self.spawn_with_retry(&mut command).await;
+        self.metrics
+            .record_spawn_latency(spawn_started_at.elapsed().as_millis() as u64);
+        // The child (if one was spawned) already `dup2`'d its own copy of
+        // each write end onto the target fd during `fork`, before
+        // `Command::spawn` returned above, so the parent's copy can be
+        // closed unconditionally here without racing the child's own setup.
+        crate::process_group::close_extra_fd_write_ends(&extra_fd_write_fds);
+        let mut child = match spawn_result {
+            Ok(child) => ChildGuard(child),
+            Err(err) => {
+                tracing::error!(error = %err, "failed to spawn codex CLI");
+                if err.kind() == std::io::ErrorKind::NotFound {
+                    let source = if req.cli_path.is_some() {
+                        "the request's cli_path override"
+                    } else {
+                        "--cli-path"
+                    };
+                    return Err(cli_unavailable_detail(format!(
+                        "codex CLI not found at {cli_path:?} (resolved from {source}): {err}"
+                    )));
+                }
+                return Err(spawn_failed_detail(format!("failed to spawn codex CLI: {err}")));
+            }
+        };
+        let pid = child.id().unwrap_or(0);
+        tracing::info!(pid, "codex CLI spawned");
+
+        if let Some(cgroup_parent) = &self.cgroup_parent {
+            if let Err(err) = crate::process_group::add_to_cgroup(cgroup_parent, pid) {
+                // `child` is a `ChildGuard`; returning here drops it and
+                // kills the process group we just failed to place.
+                return Err(spawn_failed_detail(format!(
+                    "failed to place pid {pid} in cgroup {}: {err}",
+                    cgroup_parent.display()
+                )));
+            }
+        }
+
+        let _active_request_guard =
+            ActiveRequestGuard::new(Arc::clone(&self.active_children), req.request_id.clone(), pid);
+        let _running_command_guard = RunningCommandGuard::new(
+            Arc::clone(&self.running_commands),
+            pid,
+            req.request_id.clone(),
+            req.args.clone(),
+        );
+
+        let logged_stdin = self
+            .log_stdin
+            .then(|| String::from_utf8_lossy(&stdin_bytes).into_owned());
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| internal_detail("child stdin was not piped"))?;
+
+        let stdout = if stdout_redirect.is_none() {
+            Some(
+                child
+                    .stdout
+                    .take()
+                    .ok_or_else(|| internal_detail("child stdout was not piped"))?,
+            )
+        } else {
+            None
+        };
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| internal_detail("child stderr was not piped"))?;
+
+        let extra_fd_pipes: Vec<(u32, tokio::fs::File)> = extra_fd_pipes
+            .into_iter()
+            .map(|(fd, file)| (fd, tokio::fs::File::from_std(file)))
+            .collect();
+        let extra_fd_captured: Vec<(u32, Mutex<CapturedOutput>)> = extra_fd_pipes
+            .iter()
+            .map(|(fd, _)| (*fd, Mutex::new(CapturedOutput::default())))
+            .collect();
+
+        let max_output_bytes = self.max_output_bytes;
+        let effective_timeout = match (req.timeout_ms.map(Duration::from_millis), grpc_deadline) {
+            (Some(explicit), Some(deadline)) => Some(explicit.min(deadline)),
+            (Some(explicit), None) => Some(explicit),
+            (None, Some(deadline)) => Some(deadline),
+            (None, None) => None,
+        };
+
+        // On a timeout, the killed-command response is built by `$on_timeout`
+        // from whatever's in the `CapturedOutput` buffers rather than by
+        // propagating an error, so operators can see why a command hung
+        // instead of getting back nothing at all. `$status` is bound to the
+        // exit status of the now-killed child so `$on_timeout` can still
+        // report `exit_code`/`terminating_signal` the normal way.
+        macro_rules! await_with_deadline {
+            ($run:expr, |$status:ident| $on_timeout:expr) => {
+                match effective_timeout {
+                    Some(timeout) => match tokio::time::timeout(timeout, $run).await {
+                        Ok(result) => result.map_err(|err| {
+                            internal_detail(format!("failed to run codex CLI: {err}"))
+                        })?,
+                        Err(_) => {
+                            let $status =
+                                kill_child_with_grace(&mut child, self.term_grace).await.map_err(|err| {
+                                    internal_detail(format!("failed to wait for killed codex CLI: {err}"))
+                                })?;
+                            return Ok(Response::new($on_timeout));
+                        }
+                    },
+                    None => $run.await.map_err(|err| {
+                        internal_detail(format!("failed to run codex CLI: {err}"))
+                    })?,
+                }
+            };
+        }
+
+        let response = if let Some(stdout_path) = &stdout_redirect {
+            let stderr_captured =
+                Mutex::new(new_captured_output(req.expected_output_bytes, req.tail_bytes, max_output_bytes));
+            let stdin_truncated = Mutex::new(false);
+            let (_, _, _, status) = await_with_deadline!(
+                tokio::try_join!(
+                    read_stream(stderr, max_output_bytes, &stderr_captured),
+                    write_stdin(stdin, stdin_bytes, &stdin_truncated),
+                    read_extra_fds(extra_fd_pipes, max_output_bytes, &extra_fd_captured),
+                    child.wait(),
+                ),
+                |status| {
+                    let (stderr, stderr_truncated) =
+                        stderr_captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+                    let stdin_truncated = *stdin_truncated.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    let stdout_bytes_written = std::fs::metadata(stdout_path).ok().map(|m| m.len());
+                    let finished_at_millis = unix_millis_now();
+                    let rusage = crate::process_group::children_resource_usage().since(rusage_before);
+                    RunCommandResponse {
+                        stdout: Vec::new(),
+                        stderr,
+                        exit_code: status.code().unwrap_or(-1),
+                        stdout_truncated: false,
+                        stderr_truncated,
+                        stdin_truncated,
+                        pid,
+                        terminating_signal: terminating_signal(&status),
+                        merged_output: Vec::new(),
+                        merged_output_truncated: false,
+                        dry_run: None,
+                        stdout_utf8: None,
+                        stderr_utf8: None,
+                        merged_output_utf8: None,
+                        stdout_base64: None,
+                        stderr_base64: None,
+                        merged_output_base64: None,
+                        stdout_bytes_written,
+                        started_at_millis: Some(started_at_millis),
+                        finished_at_millis: Some(finished_at_millis),
+                        user_cpu_ms: Some(rusage.user_cpu_ms),
+                        system_cpu_ms: Some(rusage.system_cpu_ms),
+                        max_rss_kb: Some(rusage.max_rss_kb),
+                        queue_wait_ms: Some(queue_wait_ms),
+                        terminated: true,
+                        extra_fd_outputs: extra_fd_outputs(&req.extra_fds, &extra_fd_captured),
+                        tag: None,
+                        attempt_count: None,
+                        error: None,
+                    }
+                }
+            );
+            let (stderr, stderr_truncated) =
+                stderr_captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+            let stdin_truncated = *stdin_truncated.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let finished_at_millis = unix_millis_now();
+            let rusage = crate::process_group::children_resource_usage().since(rusage_before);
+            let stdout_bytes_written = std::fs::metadata(stdout_path).ok().map(|m| m.len());
+            RunCommandResponse {
+                stdout: Vec::new(),
+                stderr,
+                exit_code: status.code().unwrap_or(-1),
+                stdout_truncated: false,
+                stderr_truncated,
+                stdin_truncated,
+                pid,
+                terminating_signal: terminating_signal(&status),
+                merged_output: Vec::new(),
+                merged_output_truncated: false,
+                dry_run: None,
+                stdout_utf8: None,
+                stderr_utf8: None,
+                merged_output_utf8: None,
+                stdout_base64: None,
+                stderr_base64: None,
+                merged_output_base64: None,
+                stdout_bytes_written,
+                started_at_millis: Some(started_at_millis),
+                finished_at_millis: Some(finished_at_millis),
+                user_cpu_ms: Some(rusage.user_cpu_ms),
+                system_cpu_ms: Some(rusage.system_cpu_ms),
+                max_rss_kb: Some(rusage.max_rss_kb),
+                queue_wait_ms: Some(queue_wait_ms),
+                terminated: false,
+                extra_fd_outputs: extra_fd_outputs(&req.extra_fds, &extra_fd_captured),
+                tag: None,
+                attempt_count: None,
+                error: None,
+            }
+        } else if req.merge_streams {
+            let stdout = stdout.ok_or_else(|| internal_detail("child stdout was not piped"))?;
+            let merged_captured =
+                Mutex::new(new_captured_output(req.expected_output_bytes, req.tail_bytes, max_output_bytes));
+            let stdin_truncated = Mutex::new(false);
+            let (_, _, _, status) = await_with_deadline!(
+                tokio::try_join!(
+                    read_streams_merged(stdout, stderr, max_output_bytes, &merged_captured),
+                    write_stdin(stdin, stdin_bytes, &stdin_truncated),
+                    read_extra_fds(extra_fd_pipes, max_output_bytes, &extra_fd_captured),
+                    child.wait(),
+                ),
+                |status| {
+                    let (merged_output, merged_output_truncated) =
+                        merged_captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+                    let stdin_truncated = *stdin_truncated.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    let finished_at_millis = unix_millis_now();
+                    let rusage = crate::process_group::children_resource_usage().since(rusage_before);
+                    RunCommandResponse {
+                        stdout: Vec::new(),
+                        stderr: Vec::new(),
+                        exit_code: status.code().unwrap_or(-1),
+                        stdout_truncated: false,
+                        stderr_truncated: false,
+                        stdin_truncated,
+                        pid,
+                        terminating_signal: terminating_signal(&status),
+                        merged_output,
+                        merged_output_truncated,
+                        dry_run: None,
+                        stdout_utf8: None,
+                        stderr_utf8: None,
+                        merged_output_utf8: None,
+                        stdout_base64: None,
+                        stderr_base64: None,
+                        merged_output_base64: None,
+                        stdout_bytes_written: None,
+                        started_at_millis: Some(started_at_millis),
+                        finished_at_millis: Some(finished_at_millis),
+                        user_cpu_ms: Some(rusage.user_cpu_ms),
+                        system_cpu_ms: Some(rusage.system_cpu_ms),
+                        max_rss_kb: Some(rusage.max_rss_kb),
+                        queue_wait_ms: Some(queue_wait_ms),
+                        terminated: true,
+                        extra_fd_outputs: extra_fd_outputs(&req.extra_fds, &extra_fd_captured),
+                        tag: None,
+                        attempt_count: None,
+                        error: None,
+                    }
+                }
+            );
+            let (merged_output, merged_output_truncated) =
+                merged_captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+            let stdin_truncated = *stdin_truncated.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let finished_at_millis = unix_millis_now();
+            let rusage = crate::process_group::children_resource_usage().since(rusage_before);
+            RunCommandResponse {
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+                exit_code: status.code().unwrap_or(-1),
+                stdout_truncated: false,
+                stderr_truncated: false,
+                stdin_truncated,
+                pid,
+                terminating_signal: terminating_signal(&status),
+                merged_output,
+                merged_output_truncated,
+                dry_run: None,
+                stdout_utf8: None,
+                stderr_utf8: None,
+                merged_output_utf8: None,
+                stdout_base64: None,
+                stderr_base64: None,
+                merged_output_base64: None,
+                stdout_bytes_written: None,
+                started_at_millis: Some(started_at_millis),
+                finished_at_millis: Some(finished_at_millis),
+                user_cpu_ms: Some(rusage.user_cpu_ms),
+                system_cpu_ms: Some(rusage.system_cpu_ms),
+                max_rss_kb: Some(rusage.max_rss_kb),
+                queue_wait_ms: Some(queue_wait_ms),
+                terminated: false,
+                extra_fd_outputs: extra_fd_outputs(&req.extra_fds, &extra_fd_captured),
+                tag: None,
+                attempt_count: None,
+                error: None,
+            }
+        } else {
+            let stdout = stdout.ok_or_else(|| internal_detail("child stdout was not piped"))?;
+            let stdout_captured =
+                Mutex::new(new_captured_output(req.expected_output_bytes, req.tail_bytes, max_output_bytes));
+            let stderr_captured =
+                Mutex::new(new_captured_output(req.expected_output_bytes, req.tail_bytes, max_output_bytes));
+            let stdin_truncated = Mutex::new(false);
+            let (_, _, _, _, status) = await_with_deadline!(
+                tokio::try_join!(
+                    read_stream(stdout, max_output_bytes, &stdout_captured),
+                    read_stream(stderr, max_output_bytes, &stderr_captured),
+                    write_stdin(stdin, stdin_bytes, &stdin_truncated),
+                    read_extra_fds(extra_fd_pipes, max_output_bytes, &extra_fd_captured),
+                    child.wait(),
+                ),
+                |status| {
+                    let (stdout, stdout_truncated) =
+                        stdout_captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+                    let (stderr, stderr_truncated) =
+                        stderr_captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+                    let stdin_truncated = *stdin_truncated.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    let finished_at_millis = unix_millis_now();
+                    let rusage = crate::process_group::children_resource_usage().since(rusage_before);
+                    RunCommandResponse {
+                        stdout,
+                        stderr,
+                        exit_code: status.code().unwrap_or(-1),
+                        stdout_truncated,
+                        stderr_truncated,
+                        stdin_truncated,
+                        pid,
+                        terminating_signal: terminating_signal(&status),
+                        merged_output: Vec::new(),
+                        merged_output_truncated: false,
+                        dry_run: None,
+                        stdout_utf8: None,
+                        stderr_utf8: None,
+                        merged_output_utf8: None,
+                        stdout_base64: None,
+                        stderr_base64: None,
+                        merged_output_base64: None,
+                        stdout_bytes_written: None,
+                        started_at_millis: Some(started_at_millis),
+                        finished_at_millis: Some(finished_at_millis),
+                        user_cpu_ms: Some(rusage.user_cpu_ms),
+                        system_cpu_ms: Some(rusage.system_cpu_ms),
+                        max_rss_kb: Some(rusage.max_rss_kb),
+                        queue_wait_ms: Some(queue_wait_ms),
+                        terminated: true,
+                        extra_fd_outputs: extra_fd_outputs(&req.extra_fds, &extra_fd_captured),
+                        tag: None,
+                        attempt_count: None,
+                        error: None,
+                    }
+                }
+            );
+            let (stdout, stdout_truncated) =
+                stdout_captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+            let (stderr, stderr_truncated) =
+                stderr_captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+            let stdin_truncated = *stdin_truncated.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let finished_at_millis = unix_millis_now();
+            let rusage = crate::process_group::children_resource_usage().since(rusage_before);
+            RunCommandResponse {
+                stdout,
+                stderr,
+                exit_code: status.code().unwrap_or(-1),
+                stdout_truncated,
+                stderr_truncated,
+                stdin_truncated,
+                pid,
+                terminating_signal: terminating_signal(&status),
+                merged_output: Vec::new(),
+                merged_output_truncated: false,
+                dry_run: None,
+                stdout_utf8: None,
+                stderr_utf8: None,
+                merged_output_utf8: None,
+                stdout_base64: None,
+                stderr_base64: None,
+                merged_output_base64: None,
+                stdout_bytes_written: None,
+                started_at_millis: Some(started_at_millis),
+                finished_at_millis: Some(finished_at_millis),
+                user_cpu_ms: Some(rusage.user_cpu_ms),
+                system_cpu_ms: Some(rusage.system_cpu_ms),
+                max_rss_kb: Some(rusage.max_rss_kb),
+                queue_wait_ms: Some(queue_wait_ms),
+                terminated: false,
+                extra_fd_outputs: extra_fd_outputs(&req.extra_fds, &extra_fd_captured),
+                tag: None,
+                attempt_count: None,
+                error: None,
+            }
+        };
+
+        let response = if req.decode_utf8 {
+            RunCommandResponse {
+                stdout_utf8: Some(String::from_utf8_lossy(&response.stdout).into_owned()),
+                stderr_utf8: Some(String::from_utf8_lossy(&response.stderr).into_owned()),
+                merged_output_utf8: req
+                    .merge_streams
+                    .then(|| String::from_utf8_lossy(&response.merged_output).into_owned()),
+                ..response
+            }
+        } else {
+            response
+        };
+
+        let response = if req.encode_base64 {
+            use base64::Engine as _;
+            RunCommandResponse {
+                stdout_base64: Some(base64::engine::general_purpose::STANDARD.encode(&response.stdout)),
+                stderr_base64: Some(base64::engine::general_purpose::STANDARD.encode(&response.stderr)),
+                merged_output_base64: req
+                    .merge_streams
+                    .then(|| base64::engine::general_purpose::STANDARD.encode(&response.merged_output)),
+                ..response
+            }
+        } else {
+            response
+        };
+
+        let response = RunCommandResponse { tag: req.tag.clone(), ..response };
+
+        tracing::info!(
+            exit_code = response.exit_code,
+            elapsed_ms = started_at.elapsed().as_millis() as u64,
+            "run_command completed"
+        );
+
+        if let Some(access_log_handle) = &self.access_log_handle {
+            access_log_handle.log(crate::access_log::AccessLogEntry {
+                timestamp_millis: started_at_millis,
+                request_id: req.request_id.clone(),
+                args: req.args.clone(),
+                cwd: req.cwd.clone(),
+                exit_code: response.exit_code,
+                duration_ms: started_at.elapsed().as_millis() as u64,
+                stdin: logged_stdin,
+                env: self.redact_env_for_log(&allowed_env),
+                tag: req.tag.clone(),
+            });
+        }
+
+        Ok(Response::new(response))
+    }
+}
+
+#[tonic::async_trait]
+impl CodexCli for CodexCliService {
+    #[tracing::instrument(
+        name = "codex_cli.run_command",
+        skip_all,
+        fields(
+            request_id = %uuid::Uuid::new_v4(),
+            cli_path = %self.cli_path.display(),
+            arg_count = request.get_ref().args.len(),
+            cwd = %request.get_ref().cwd,
+            tag = %request.get_ref().tag.as_deref().unwrap_or(""),
+        )
+    )]
+    async fn run_command(
+        &self,
+        request: Request<RunCommandRequest>,
+    ) -> Result<Response<RunCommandResponse>, Status> {
+        self.metrics.record_start();
+        let started_at = std::time::Instant::now();
+        let (metadata, _extensions, req) = request.into_parts();
+        let max_retries = req.max_retries.unwrap_or(0);
+        let mut attempt: u32 = 0;
+        let result = loop {
+            let result = self.run_command_impl(req.clone(), &metadata, started_at).await;
+            let should_retry = attempt < max_retries
+                && matches!(
+                    &result,
+                    Ok(response) if req.retry_on_exit_codes.contains(&response.get_ref().exit_code)
+                );
+            if !should_retry {
+                break result.map(|mut response| {
+                    response.get_mut().attempt_count = Some(attempt + 1);
+                    response
+                });
+            }
+            let delay = Duration::from_millis(100 << attempt.min(10));
+            tracing::warn!(
+                exit_code = ?result.as_ref().ok().map(|response| response.get_ref().exit_code),
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "run_command exited with a retryable code; retrying"
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        };
+        let status_code = match &result {
+            Ok(_) => "OK".to_string(),
+            Err(status) => format!("{:?}", status.code()),
+        };
+        self.metrics
+            .record_completion(&status_code, started_at.elapsed().as_millis() as u64);
+        result
+    }
+
+    type StreamCommandStream =
+        Pin<Box<dyn tokio_stream::Stream<Item = Result<StreamCommandChunk, Status>> + Send>>;
+
+    async fn stream_command(
+        &self,
+        request: Request<StreamCommandRequest>,
+    ) -> Result<Response<Self::StreamCommandStream>, Status> {
+        let permit = self.acquire_permit().await?;
+
+        let req = request.into_inner();
+        self.validate_cwd(&req.cwd)?;
+        self.validate_args(&req.args)?;
+        self.validate_command_allowed(&req.args)?;
+        let chunk_size = req
+            .chunk_size
+            .map(|size| size as usize)
+            .unwrap_or(DEFAULT_STREAM_CHUNK_SIZE);
+        let split_lines = req.split_lines.unwrap_or(false);
+        let global_sequence = req.global_sequence.unwrap_or(false);
+        let flush_interval = req
+            .flush_interval_ms
+            .filter(|&ms| ms > 0)
+            .map(|ms| Duration::from_millis(ms as u64));
+        let progress_prefix = req.progress_prefix.clone();
+        if progress_prefix.is_some() && !split_lines {
+            return Err(invalid_argument_detail(
+                "progress_prefix requires split_lines to be set",
+            ));
+        }
+
+        let mut command = self.command_for(&self.cli_path, &req.args, &req.cwd);
+        self.strip_proxy_env(&mut command);
+        let mut child = command
+            .spawn()
+            .map_err(|err| spawn_failed_detail(format!("failed to spawn codex CLI: {err}")))?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| internal_detail("child stdout was not piped"))?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| internal_detail("child stderr was not piped"))?;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        tokio::spawn(async move {
+            forward_streamed_output(
+                child,
+                stdout,
+                stderr,
+                chunk_size,
+                split_lines,
+                global_sequence,
+                flush_interval,
+                progress_prefix,
+                tx,
+            )
+            .await;
+            drop(permit);
+        });
+
+        Ok(Response::new(Box::pin(
+            tokio_stream::wrappers::ReceiverStream::new(rx),
+        )))
+    }
+
+    async fn run_interactive_command(
+        &self,
+        request: Request<Streaming<InteractiveCommandInput>>,
+    ) -> Result<Response<RunCommandResponse>, Status> {
+        let entered_at = std::time::Instant::now();
+        let metadata = request.metadata().clone();
+        self.acquire_spawn_rate_token().await?;
+        let _permit = self.acquire_permit().await?;
+        let queue_wait_ms = entered_at.elapsed().as_millis() as u64;
+        let mut stream = request.into_inner();
+
+        let start = match stream.message().await? {
+            Some(InteractiveCommandInput {
+                input: Some(Input::Start(start)),
+            }) => start,
+            Some(_) => {
+                return Err(invalid_argument_detail(
+                    "the first message on the stream must be `start`",
+                ));
+            }
+            None => return Err(invalid_argument_detail("stream closed before `start`")),
+        };
+
+        // Fields `run_command` supports but this streaming RPC has no
+        // sensible way to honor (they assume a single atomic spawn-then-wait,
+        // not one interleaved with an open stdin stream) are rejected up
+        // front instead of being silently dropped.
+        if start.nice.is_some() {
+            return Err(invalid_argument_detail(
+                "run_interactive_command does not support nice; use run_command instead",
+            ));
+        }
+        if start.umask.is_some() {
+            return Err(invalid_argument_detail(
+                "run_interactive_command does not support umask; use run_command instead",
+            ));
+        }
+        if !start.cpu_affinity.is_empty() {
+            return Err(invalid_argument_detail(
+                "run_interactive_command does not support cpu_affinity; use run_command instead",
+            ));
+        }
+        if !start.extra_fds.is_empty() {
+            return Err(invalid_argument_detail(
+                "run_interactive_command does not support extra_fds; use run_command instead",
+            ));
+        }
+        if start.stdout_file_path.is_some() {
+            return Err(invalid_argument_detail(
+                "run_interactive_command does not support stdout_file_path; use run_command instead",
+            ));
+        }
+        if start.stdin_file_path.is_some() {
+            return Err(invalid_argument_detail(
+                "run_interactive_command does not support stdin_file_path; use run_command instead",
+            ));
+        }
+        if start.encode_base64 {
+            return Err(invalid_argument_detail(
+                "run_interactive_command does not support encode_base64; use run_command instead",
+            ));
+        }
+        if start.max_retries.is_some() || !start.retry_on_exit_codes.is_empty() {
+            return Err(invalid_argument_detail(
+                "run_interactive_command does not support max_retries/retry_on_exit_codes; use run_command instead",
+            ));
+        }
+        if start.merge_streams {
+            return Err(invalid_argument_detail(
+                "run_interactive_command does not support merge_streams; use run_command instead",
+            ));
+        }
+        if start.dry_run {
+            return Err(invalid_argument_detail(
+                "run_interactive_command does not support dry_run; use run_command instead",
+            ));
+        }
+
+        let _per_client_permit = self.acquire_per_client_permit(start.client_id.as_deref())?;
+        self.metrics.record_tag(start.tag.as_deref());
+        let grpc_deadline = grpc_timeout_from_metadata(&metadata);
+        let effective_timeout = match (start.timeout_ms.map(Duration::from_millis), grpc_deadline) {
+            (Some(explicit), Some(deadline)) => Some(explicit.min(deadline)),
+            (Some(explicit), None) => Some(explicit),
+            (None, Some(deadline)) => Some(deadline),
+            (None, None) => None,
+        };
+        self.validate_cwd(&start.cwd)?;
+        self.validate_args(&start.args)?;
+        self.validate_command_allowed(&start.args)?;
+        let (valid_env, malformed_env) = self.validate_env_keys(&start.env)?;
+        for name in &malformed_env {
+            tracing::warn!(env_var = name, "dropped malformed environment variable name");
+        }
+        let (allowed_env, rejected_env) = self.filter_env(&valid_env);
+        for name in &rejected_env {
+            tracing::warn!(env_var = name, "rejected disallowed environment variable");
+        }
+        if self.reject_disallowed_env && !rejected_env.is_empty() {
+            return Err(invalid_argument_detail(format!(
+                "environment variables not in --env-allowlist: {}",
+                rejected_env.join(", ")
+            )));
+        }
+
+        let resource_limits = crate::process_group::ResourceLimits {
+            max_cpu_seconds: start.max_cpu_seconds,
+            max_memory_bytes: start.max_memory_bytes,
+            max_open_files: start.max_open_files,
+        };
+        if !resource_limits.is_empty() && !self.allow_rlimits {
+            return Err(permission_denied_detail(
+                "max_cpu_seconds/max_memory_bytes/max_open_files require the server to be started with --allow-rlimits",
+            ));
+        }
+
+        let cli_path = self.resolve_cli_path(start.cli_path.as_deref())?;
+        self.validate_cli_path_is_executable(cli_path)?;
+        let mut command = self.command_for(cli_path, &start.args, &start.cwd);
+        if start.clear_env {
+            command.env_clear();
+        } else if start.inherit_server_env {
+            command.envs(std::env::vars());
+        }
+        command.envs(&self.default_env);
+        command.envs(allowed_env.iter().map(|(name, value)| (*name, *value)));
+        self.strip_proxy_env(&mut command);
+        crate::process_group::apply_rlimits(&mut command, resource_limits);
+        let logged_stdin = self.log_stdin.then(|| String::from_utf8_lossy(&start.stdin).into_owned());
+        let started_at_millis = unix_millis_now();
+        let rusage_before = crate::process_group::children_resource_usage();
+        let spawn_started_at = std::time::Instant::now();
+        let spawn_result = self.spawn_with_retry(&mut command).await;
+        self.metrics
+            .record_spawn_latency(spawn_started_at.elapsed().as_millis() as u64);
+        let mut child = ChildGuard(spawn_result.map_err(|err| {
+            spawn_failed_detail(format!("failed to spawn codex CLI: {err}"))
+        })?);
+        let pid = child.id().unwrap_or(0);
+        if let Some(cgroup_parent) = &self.cgroup_parent {
+            if let Err(err) = crate::process_group::add_to_cgroup(cgroup_parent, pid) {
+                // `child` is a `ChildGuard`; returning here drops it and
+                // kills the process group we just failed to place.
+                return Err(spawn_failed_detail(format!(
+                    "failed to place pid {pid} in cgroup {}: {err}",
+                    cgroup_parent.display()
+                )));
+            }
+        }
+        let _active_request_guard =
+            ActiveRequestGuard::new(Arc::clone(&self.active_children), start.request_id.clone(), pid);
+        let _running_command_guard = RunningCommandGuard::new(
+            Arc::clone(&self.running_commands),
+            pid,
+            start.request_id.clone(),
+            start.args.clone(),
+        );
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| internal_detail("child stdin was not piped"))?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| internal_detail("child stdout was not piped"))?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| internal_detail("child stderr was not piped"))?;
+
+        let max_output_bytes = self.max_output_bytes;
+        let stdout_captured =
+            Mutex::new(new_captured_output(start.expected_output_bytes, start.tail_bytes, max_output_bytes));
+        let stderr_captured =
+            Mutex::new(new_captured_output(start.expected_output_bytes, start.tail_bytes, max_output_bytes));
+        let stream_error: Mutex<Option<Status>> = Mutex::new(None);
+        let max_request_bytes = self.max_request_bytes;
+        // Mirrors `run_command_impl`'s deadline handling: a timeout kills the
+        // child (escalating through `term_grace` like any other timeout
+        // kill) and still returns whatever was captured so far, marked
+        // `terminated`, instead of propagating an error.
+        let (status, terminated) = match effective_timeout {
+            Some(timeout) => match tokio::time::timeout(
+                timeout,
+                async {
+                    tokio::try_join!(
+                        read_stream(stdout, max_output_bytes, &stdout_captured),
+                        read_stream(stderr, max_output_bytes, &stderr_captured),
+                        forward_interactive_stdin(&mut stream, stdin, start.stdin, max_request_bytes, &stream_error),
+                        child.wait(),
+                    )
+                },
+            )
+            .await
+            {
+                Ok(result) => (
+                    result.map_err(|err| internal_detail(format!("failed to run codex CLI: {err}")))?.3,
+                    false,
+                ),
+                Err(_) => (
+                    kill_child_with_grace(&mut child, self.term_grace).await.map_err(|err| {
+                        internal_detail(format!("failed to wait for killed codex CLI: {err}"))
+                    })?,
+                    true,
+                ),
+            },
+            None => (
+                tokio::try_join!(
+                    read_stream(stdout, max_output_bytes, &stdout_captured),
+                    read_stream(stderr, max_output_bytes, &stderr_captured),
+                    forward_interactive_stdin(&mut stream, stdin, start.stdin, max_request_bytes, &stream_error),
+                    child.wait(),
+                )
+                .map_err(|err| internal_detail(format!("failed to run codex CLI: {err}")))?
+                .3,
+                false,
+            ),
+        };
+        if let Some(status) = stream_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take() {
+            return Err(status);
+        }
+        let (stdout, stdout_truncated) =
+            stdout_captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+        let (stderr, stderr_truncated) =
+            stderr_captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+        let finished_at_millis = unix_millis_now();
+        let rusage = crate::process_group::children_resource_usage().since(rusage_before);
+
+        let response = RunCommandResponse {
+            stdout_utf8: start
+                .decode_utf8
+                .then(|| String::from_utf8_lossy(&stdout).into_owned()),
+            stderr_utf8: start
+                .decode_utf8
+                .then(|| String::from_utf8_lossy(&stderr).into_owned()),
+            stdout,
+            stderr,
+            exit_code: status.code().unwrap_or(-1),
+            stdout_truncated,
+            stderr_truncated,
+            stdin_truncated: false,
+            pid,
+            terminating_signal: terminating_signal(&status),
+            merged_output: Vec::new(),
+            merged_output_truncated: false,
+            merged_output_utf8: None,
+            stdout_base64: None,
+            stderr_base64: None,
+            merged_output_base64: None,
+            dry_run: None,
+            stdout_bytes_written: None,
+            started_at_millis: Some(started_at_millis),
+            finished_at_millis: Some(finished_at_millis),
+            user_cpu_ms: Some(rusage.user_cpu_ms),
+            system_cpu_ms: Some(rusage.system_cpu_ms),
+            max_rss_kb: Some(rusage.max_rss_kb),
+            queue_wait_ms: Some(queue_wait_ms),
+            terminated,
+            extra_fd_outputs: Vec::new(),
+            tag: start.tag.clone(),
+            attempt_count: None,
+            error: None,
+        };
+
+        tracing::info!(
+            exit_code = response.exit_code,
+            elapsed_ms = entered_at.elapsed().as_millis() as u64,
+            "run_interactive_command completed"
+        );
+
+        if let Some(access_log_handle) = &self.access_log_handle {
+            access_log_handle.log(crate::access_log::AccessLogEntry {
+                timestamp_millis: started_at_millis,
+                request_id: start.request_id.clone(),
+                args: start.args.clone(),
+                cwd: start.cwd.clone(),
+                exit_code: response.exit_code,
+                duration_ms: entered_at.elapsed().as_millis() as u64,
+                stdin: logged_stdin,
+                env: self.redact_env_for_log(&allowed_env),
+                tag: start.tag.clone(),
+            });
+        }
+
+        Ok(Response::new(response))
+    }
+
+    async fn describe_server(
+        &self,
+        _request: Request<DescribeServerRequest>,
+    ) -> Result<Response<DescribeServerResponse>, Status> {
+        Ok(Response::new(DescribeServerResponse {
+            default_cli_path: self.cli_path.display().to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            concurrency_limit: self.concurrency_limit_value.map(|limit| limit as u32),
+            env_allowlist_active: self.env_allowlist.is_some(),
+            cli_override_allowed: self.allow_cli_override,
+            default_cli_path_source: self.cli_path_source as i32,
+            default_env_keys: self.default_env.keys().cloned().collect(),
+        }))
+    }
+
+    async fn ping(
+        &self,
+        _request: Request<crate::proto::PingRequest>,
+    ) -> Result<Response<crate::proto::PingResponse>, Status> {
+        Ok(Response::new(crate::proto::PingResponse {
+            timestamp_millis: unix_millis_now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }))
+    }
+
+    async fn cancel_command(
+        &self,
+        request: Request<crate::proto::CancelCommandRequest>,
+    ) -> Result<Response<crate::proto::CancelCommandResponse>, Status> {
+        let req = request.into_inner();
+        let pid = self
+            .active_children
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&req.request_id);
+        let found = match pid {
+            Some(pid) => {
+                tracing::info!(pid, request_id = %req.request_id, "cancelling command");
+                kill_pid(pid);
+                true
+            }
+            None => false,
+        };
+        Ok(Response::new(crate::proto::CancelCommandResponse { found }))
+    }
+
+    async fn list_running(
+        &self,
+        request: Request<crate::proto::ListRunningRequest>,
+    ) -> Result<Response<crate::proto::ListRunningResponse>, Status> {
+        let req = request.into_inner();
+        let commands = self
+            .running_commands
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .map(|(&pid, entry)| crate::proto::RunningCommand {
+                request_id: entry.request_id.clone(),
+                args: if req.redact_args { Vec::new() } else { entry.args.clone() },
+                pid,
+                elapsed_ms: entry.started_at.elapsed().as_millis() as u64,
+            })
+            .collect();
+        Ok(Response::new(crate::proto::ListRunningResponse { commands }))
+    }
+
+    /// Starts the same graceful drain a `SIGTERM` triggers, then returns
+    /// without waiting for it to finish; see [`crate::run_server`] for how
+    /// `shutdown` being cancelled actually stops the server.
+    async fn shutdown_server(
+        &self,
+        request: Request<crate::proto::ShutdownServerRequest>,
+    ) -> Result<Response<crate::proto::ShutdownServerResponse>, Status> {
+        let req = request.into_inner();
+        if let Some(expected) = &self.shutdown_token {
+            if req.token.as_deref() != Some(expected.as_str()) {
+                return Err(permission_denied_detail(
+                    "shutdown_server requires a token matching --shutdown-token",
+                ));
+            }
+        }
+        tracing::info!("shutdown_server called; draining in-flight requests");
+        self.shutdown.cancel();
+        Ok(Response::new(crate::proto::ShutdownServerResponse {}))
+    }
+
+    async fn run_commands(
+        &self,
+        request: Request<RunCommandsRequest>,
+    ) -> Result<Response<RunCommandsResponse>, Status> {
+        let req = request.into_inner();
+        let stop_on_first_failure = req.stop_on_first_failure;
+        // Each `run_command` call already waits on `--concurrency-limit`
+        // itself; this just bounds how many of them are polled at once so a
+        // huge batch doesn't pile up far more pending requests against that
+        // semaphore than the limit allows. Unlimited when no limit is
+        // configured, same as a limitless `--concurrency-limit`.
+        let concurrency = self.concurrency_limit_value.unwrap_or(req.requests.len()).max(1);
+
+        // Driven by hand instead of `.buffered(concurrency)` so that
+        // stopping early on a failure can stop *dispatching new* requests
+        // without cancelling ones already in flight: dropping a `Buffered`
+        // stream drops every future it's still holding, and `ChildGuard`
+        // kills its process group on drop, which would kill children the
+        // proto doc promises are "allowed to finish". `pending` is polled
+        // to completion even after `stop_dispatch` is set; slots are
+        // indexed by the request's position so the result order matches
+        // `req.requests` regardless of which one finishes first.
+        let mut requests = req.requests.into_iter().enumerate();
+        let mut responses: Vec<Option<RunCommandResponse>> = vec![None; requests.len()];
+        let mut pending = futures::stream::FuturesUnordered::new();
+        let mut stop_dispatch = false;
+        let dispatch = |index: usize, inner: RunCommandRequest| {
+            let this = self;
+            async move { (index, this.run_command(Request::new(inner)).await) }
+        };
+        for (index, inner) in requests.by_ref().take(concurrency) {
+            pending.push(dispatch(index, inner));
+        }
+
+        while let Some((index, result)) = pending.next().await {
+            // An RPC-level failure (a disallowed `cwd`, a spawn failure,
+            // `resource_exhausted` from the concurrency limiter, ...) is
+            // routine under the load this batch RPC exists to handle, and
+            // must not discard the responses already collected for the
+            // rest of the batch the way propagating it with `?` would.
+            let (response, failed) = match result {
+                Ok(response) => {
+                    let response = response.into_inner();
+                    let failed = response.exit_code != 0;
+                    (response, failed)
+                }
+                Err(status) => (run_command_response_for_status(&status), true),
+            };
+            responses[index] = Some(response);
+            if stop_on_first_failure && failed {
+                stop_dispatch = true;
+            }
+            if !stop_dispatch {
+                if let Some((index, inner)) = requests.next() {
+                    pending.push(dispatch(index, inner));
+                }
+            }
+        }
+        let responses = responses.into_iter().flatten().collect();
+        Ok(Response::new(RunCommandsResponse { responses }))
+    }
+}
+
+/// Accumulates bytes read from one stream and, once `split_lines` buffering
+/// is enabled, hands back only complete lines (newline stripped) until the
+/// stream hits EOF, at which point [`LineBuffer::flush`] returns whatever
+/// partial line is left over.
+#[derive(Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Appends `bytes` and returns every complete line now available,
+    /// leaving a trailing partial line (if any) buffered for next time.
+    fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        self.pending.extend_from_slice(bytes);
+        let mut lines = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|&b| b == b'\n') {
+            lines.push(self.pending.drain(..=pos).take(pos).collect());
+        }
+        lines
+    }
+
+    /// Returns the trailing partial line, if any, once the stream is done.
+    fn flush(&mut self) -> Option<Vec<u8>> {
+        if self.pending.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut self.pending))
+        }
+    }
+}
+
+/// Assigns [`StreamCommandChunk::seq`] values for [`forward_streamed_output`].
+/// In global mode there's a single counter shared by every chunk, in the
+/// order they're actually forwarded; otherwise stdout and stderr each get
+/// their own, so a caller that only watches one stream can still detect
+/// loss in it without tracking the other. See `StreamCommandRequest`'s
+/// `global_sequence` field.
+struct SeqCounter {
+    global: bool,
+    next_global: u64,
+    next_stdout: u64,
+    next_stderr: u64,
+}
+
+impl SeqCounter {
+    fn new(global: bool) -> Self {
+        Self {
+            global,
+            next_global: 0,
+            next_stdout: 0,
+            next_stderr: 0,
+        }
+    }
+
+    fn next(&mut self, stream: Stream) -> u64 {
+        let counter = if self.global {
+            &mut self.next_global
+        } else {
+            match stream {
+                Stream::Stdout => &mut self.next_stdout,
+                Stream::Stderr => &mut self.next_stderr,
+                Stream::Unspecified => &mut self.next_global,
+            }
+        };
+        let seq = *counter;
+        *counter += 1;
+        seq
+    }
+}
+
+/// Sends `chunk`, killing `child`'s process group and returning `false` if
+/// the receiver (the RPC future) has already been dropped, e.g. because the
+/// client disconnected.
+async fn send_chunk_or_kill(
+    tx: &tokio::sync::mpsc::Sender<Result<StreamCommandChunk, Status>>,
+    child: &mut Child,
+    chunk: StreamCommandChunk,
+) -> bool {
+    if tx.send(Ok(chunk)).await.is_err() {
+        kill_child_process_group(child);
+        false
+    } else {
+        true
+    }
+}
+
+/// Parses a complete stderr `line` (no trailing newline, as produced by
+/// [`LineBuffer`]) against `StreamCommandRequest.progress_prefix`, returning a
+/// `progress` chunk if it matches. Returns `None` for a non-matching line or
+/// when no prefix is configured, in which case the caller still forwards the
+/// line as an ordinary stderr chunk — this only ever adds a chunk, never
+/// replaces one.
+fn progress_chunk_for_stderr_line(
+    line: &[u8],
+    progress_prefix: Option<&str>,
+    seq: &mut SeqCounter,
+) -> Option<StreamCommandChunk> {
+    let prefix = progress_prefix?;
+    let line = std::str::from_utf8(line).ok()?;
+    let message = line.strip_prefix(prefix)?.to_string();
+    Some(StreamCommandChunk {
+        seq: seq.next(Stream::Unspecified),
+        stream: Stream::Unspecified as i32,
+        chunk: Some(Chunk::Progress(ProgressEvent { message })),
+    })
+}
+
+async fn forward_streamed_output(
+    mut child: Child,
+    mut stdout: impl AsyncRead + Unpin,
+    mut stderr: impl AsyncRead + Unpin,
+    chunk_size: usize,
+    split_lines: bool,
+    global_sequence: bool,
+    flush_interval: Option<Duration>,
+    progress_prefix: Option<String>,
+    tx: tokio::sync::mpsc::Sender<Result<StreamCommandChunk, Status>>,
+) {
+    let mut stdout_buf = vec![0u8; chunk_size];
+    let mut stderr_buf = vec![0u8; chunk_size];
+    let mut stdout_open = true;
+    let mut stderr_open = true;
+    let mut stdout_lines = LineBuffer::default();
+    let mut stderr_lines = LineBuffer::default();
+    let mut seq = SeqCounter::new(global_sequence);
+
+    // Coalescing only applies to the raw byte-chunk mode: `split_lines`
+    // already buffers up to a complete line per chunk, so there's nothing
+    // left to coalesce there.
+    let coalesce = flush_interval.is_some() && !split_lines;
+    let mut pending_stdout: Vec<u8> = Vec::new();
+    let mut pending_stderr: Vec<u8> = Vec::new();
+    let mut flush_ticker = flush_interval.map(tokio::time::interval);
+
+    while stdout_open || stderr_open {
+        tokio::select! {
+            result = stdout.read(&mut stdout_buf), if stdout_open => {
+                match result {
+                    Ok(0) => {
+                        stdout_open = false;
+                        if coalesce && !pending_stdout.is_empty() {
+                            let chunk = StreamCommandChunk {
+                                seq: seq.next(Stream::Stdout),
+                                stream: Stream::Stdout as i32,
+                                chunk: Some(Chunk::Stdout(std::mem::take(&mut pending_stdout))),
+                            };
+                            if !send_chunk_or_kill(&tx, &mut child, chunk).await {
+                                return;
+                            }
+                        } else if split_lines {
+                            if let Some(line) = stdout_lines.flush() {
+                                let chunk = StreamCommandChunk {
+                                    seq: seq.next(Stream::Stdout),
+                                    stream: Stream::Stdout as i32,
+                                    chunk: Some(Chunk::Stdout(line)),
+                                };
+                                if !send_chunk_or_kill(&tx, &mut child, chunk).await {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                    Ok(n) if coalesce => {
+                        pending_stdout.extend_from_slice(&stdout_buf[..n]);
+                        if pending_stdout.len() >= chunk_size {
+                            let chunk = StreamCommandChunk {
+                                seq: seq.next(Stream::Stdout),
+                                stream: Stream::Stdout as i32,
+                                chunk: Some(Chunk::Stdout(std::mem::take(&mut pending_stdout))),
+                            };
+                            if !send_chunk_or_kill(&tx, &mut child, chunk).await {
+                                return;
+                            }
+                        }
+                    }
+                    Ok(n) => {
+                        let out = if split_lines { stdout_lines.push(&stdout_buf[..n]) } else { vec![stdout_buf[..n].to_vec()] };
+                        for line in out {
+                            let chunk = StreamCommandChunk {
+                                seq: seq.next(Stream::Stdout),
+                                stream: Stream::Stdout as i32,
+                                chunk: Some(Chunk::Stdout(line)),
+                            };
+                            if !send_chunk_or_kill(&tx, &mut child, chunk).await {
+                                return;
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        let _ = tx.send(Err(internal_detail(format!("failed reading stdout: {err}")))).await;
+                        kill_child_process_group(&mut child);
+                        return;
+                    }
+                }
+            }
+            result = stderr.read(&mut stderr_buf), if stderr_open => {
+                match result {
+                    Ok(0) => {
+                        stderr_open = false;
+                        if coalesce && !pending_stderr.is_empty() {
+                            let chunk = StreamCommandChunk {
+                                seq: seq.next(Stream::Stderr),
+                                stream: Stream::Stderr as i32,
+                                chunk: Some(Chunk::Stderr(std::mem::take(&mut pending_stderr))),
+                            };
+                            if !send_chunk_or_kill(&tx, &mut child, chunk).await {
+                                return;
+                            }
+                        } else if split_lines {
+                            if let Some(line) = stderr_lines.flush() {
+                                let chunk = StreamCommandChunk {
+                                    seq: seq.next(Stream::Stderr),
+                                    stream: Stream::Stderr as i32,
+                                    chunk: Some(Chunk::Stderr(line.clone())),
+                                };
+                                if !send_chunk_or_kill(&tx, &mut child, chunk).await {
+                                    return;
+                                }
+                                if let Some(progress) =
+                                    progress_chunk_for_stderr_line(&line, progress_prefix.as_deref(), &mut seq)
+                                    && !send_chunk_or_kill(&tx, &mut child, progress).await
+                                {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                    Ok(n) if coalesce => {
+                        pending_stderr.extend_from_slice(&stderr_buf[..n]);
+                        if pending_stderr.len() >= chunk_size {
+                            let chunk = StreamCommandChunk {
+                                seq: seq.next(Stream::Stderr),
+                                stream: Stream::Stderr as i32,
+                                chunk: Some(Chunk::Stderr(std::mem::take(&mut pending_stderr))),
+                            };
+                            if !send_chunk_or_kill(&tx, &mut child, chunk).await {
+                                return;
+                            }
+                        }
+                    }
+                    Ok(n) => {
+                        let out = if split_lines { stderr_lines.push(&stderr_buf[..n]) } else { vec![stderr_buf[..n].to_vec()] };
+                        for line in out {
+                            let chunk = StreamCommandChunk {
+                                seq: seq.next(Stream::Stderr),
+                                stream: Stream::Stderr as i32,
+                                chunk: Some(Chunk::Stderr(line.clone())),
+                            };
+                            if !send_chunk_or_kill(&tx, &mut child, chunk).await {
+                                return;
+                            }
+                            if let Some(progress) =
+                                progress_chunk_for_stderr_line(&line, progress_prefix.as_deref(), &mut seq)
+                                && !send_chunk_or_kill(&tx, &mut child, progress).await
+                            {
+                                return;
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        let _ = tx.send(Err(internal_detail(format!("failed reading stderr: {err}")))).await;
+                        kill_child_process_group(&mut child);
+                        return;
+                    }
+                }
+            }
+            _ = async {
+                match &mut flush_ticker {
+                    Some(ticker) => { ticker.tick().await; }
+                    None => std::future::pending::<()>().await,
+                }
+            } => {
+                if !pending_stdout.is_empty() {
+                    let chunk = StreamCommandChunk {
+                        seq: seq.next(Stream::Stdout),
+                        stream: Stream::Stdout as i32,
+                        chunk: Some(Chunk::Stdout(std::mem::take(&mut pending_stdout))),
+                    };
+                    if !send_chunk_or_kill(&tx, &mut child, chunk).await {
+                        return;
+                    }
+                }
+                if !pending_stderr.is_empty() {
+                    let chunk = StreamCommandChunk {
+                        seq: seq.next(Stream::Stderr),
+                        stream: Stream::Stderr as i32,
+                        chunk: Some(Chunk::Stderr(std::mem::take(&mut pending_stderr))),
+                    };
+                    if !send_chunk_or_kill(&tx, &mut child, chunk).await {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    match child.wait().await {
+        Ok(status) => {
+            let chunk = StreamCommandChunk {
+                seq: seq.next(Stream::Unspecified),
+                stream: Stream::Unspecified as i32,
+                chunk: Some(Chunk::ExitCode(status.code().unwrap_or(-1))),
+            };
+            let _ = tx.send(Ok(chunk)).await;
+        }
+        Err(err) => {
+            let _ = tx
+                .send(Err(internal_detail(format!(
+                    "failed waiting for codex CLI: {err}"
+                ))))
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+impl CodexCliService {
+    /// Builds a `CodexCliService` with every constructor argument set to a
+    /// permissive default (no allowlists, no limits, a fresh `Metrics`), so
+    /// tests only need to name the field(s) that matter to them via the
+    /// `with_*` methods below instead of repeating all of `new`'s
+    /// positional arguments.
+    fn for_test(cli_path: impl Into<PathBuf>) -> Self {
+        Self::new(
+            cli_path.into(),
+            None,
+            false,
+            None,
+            None,
+            None,
+            None,
+            Arc::new(crate::metrics::Metrics::default()),
+            false,
+            None,
+            false,
+            4 * 1024 * 1024,
+            None,
+            false,
+            false,
+            None,
+            false,
+            0,
+            None,
+            Vec::new(),
+            None,
+            None,
+            None,
+            crate::proto::CliPathSource::Default,
+            false,
+            None,
+            CancellationToken::new(),
+            None,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            None,
+            false,
+            false,
+            HashMap::new(),
+        )
+    }
+
+    fn with_max_output_bytes(mut self, max_output_bytes: Option<usize>) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    fn with_max_request_bytes(mut self, max_request_bytes: usize) -> Self {
+        self.max_request_bytes = max_request_bytes;
+        self
+    }
+
+    fn with_allowed_cwd_root(mut self, allowed_cwd_root: Option<PathBuf>) -> Self {
+        self.allowed_cwd_root = allowed_cwd_root;
+        self
+    }
+
+    fn with_allowed_stdout_dir(mut self, allowed_stdout_dir: Option<PathBuf>) -> Self {
+        self.allowed_stdout_dir = allowed_stdout_dir;
+        self
+    }
+
+    fn with_allowed_stdin_dir(mut self, allowed_stdin_dir: Option<PathBuf>) -> Self {
+        self.allowed_stdin_dir = allowed_stdin_dir;
+        self
+    }
+
+    fn with_concurrency_limit(mut self, concurrency_limit: usize) -> Self {
+        self.concurrency_limit = Some(Arc::new(Semaphore::new(concurrency_limit)));
+        self.concurrency_limit_value = Some(concurrency_limit);
+        self
+    }
+}
+
+#[cfg(all(test, unix))]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    /// Spawns a shell that forks a sleeping grandchild, kills the shell's
+    /// process group, and checks that the grandchild does not survive.
+    #[tokio::test]
+    async fn kill_process_group_reaps_grandchild() {
+        let pid_file = tempfile::NamedTempFile::new().expect("failed to create temp file");
+
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(format!(
+            "sh -c 'sleep 60 & echo $! > {path}; wait' & echo $!; wait",
+            path = pid_file.path().display(),
+        ));
+        command.stdout(std::process::Stdio::null());
+        command.stderr(std::process::Stdio::null());
+        set_own_process_group(&mut command);
+
+        let mut child = command.spawn().expect("failed to spawn test shell");
+        // Give the grandchild time to start and record its pid.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        kill_child_process_group(&mut child);
+        let _ = child.wait().await;
+        // Allow the kernel a moment to finish tearing down the group.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let grandchild_pid = std::fs::read_to_string(pid_file.path())
+            .expect("grandchild did not record its pid")
+            .trim()
+            .parse::<i32>()
+            .expect("grandchild pid was not an integer");
+        let still_alive = unsafe { libc::kill(grandchild_pid, 0) } == 0;
+        assert!(!still_alive, "grandchild survived process-group kill");
+    }
+
+    #[tokio::test]
+    async fn run_command_truncates_output_over_the_limit() {
+        let service = CodexCliService::for_test("sh").with_max_output_bytes(Some(10));
+        let request = Request::new(RunCommandRequest {
+            args: vec!["-c".to_string(), "printf '%020d' 0".to_string()],
+            ..Default::default()
+        });
+
+        let response = service.run_command(request).await.unwrap().into_inner();
+
+        assert_eq!(response.stdout.len(), 10);
+        assert!(response.stdout_truncated);
+        assert!(!response.stderr_truncated);
+    }
+
+    /// Simulates a client disconnecting mid-call by aborting the task
+    /// polling `run_command`, the same way tonic drops the service future
+    /// when the underlying HTTP/2 stream resets. The spawned child should
+    /// not survive the abort.
+    #[tokio::test]
+    async fn dropping_the_rpc_future_kills_the_child() {
+        let service = Arc::new(CodexCliService::for_test("sh"));
+        let pid_file = tempfile::NamedTempFile::new().expect("failed to create temp file");
+        let request = Request::new(RunCommandRequest {
+            args: vec![
+                "-c".to_string(),
+                format!("echo $$ > {path}; sleep 60", path = pid_file.path().display()),
+            ],
+            ..Default::default()
+        });
+
+        let task_service = Arc::clone(&service);
+        let handle = tokio::spawn(async move { task_service.run_command(request).await });
+        // Give the child time to start and record its pid.
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        handle.abort();
+        let _ = handle.await;
+        // Allow the kernel a moment to finish tearing down the killed child.
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let child_pid = std::fs::read_to_string(pid_file.path())
+            .expect("child did not record its pid")
+            .trim()
+            .parse::<i32>()
+            .expect("child pid was not an integer");
+        let still_alive = unsafe { libc::kill(child_pid, 0) } == 0;
+        assert!(!still_alive, "child survived an aborted RunCommand call");
+    }
+
+    /// `run_interactive_command`'s analog of
+    /// `dropping_the_rpc_future_kills_the_child`: its child must not survive
+    /// the service future being dropped mid-call either. Unlike
+    /// `run_command`, this RPC takes a `Streaming<InteractiveCommandInput>`,
+    /// which only a real connection can produce, so this drives the call
+    /// over an actual Unix socket instead of invoking the trait method
+    /// in-process, then aborts the task serving that connection (standing
+    /// in for tonic dropping the handler future on a client disconnect).
+    #[tokio::test]
+    async fn dropping_the_interactive_rpc_connection_kills_the_child() {
+        let service = Arc::new(CodexCliService::for_test("sh"));
+
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let socket_path = dir.path().join("codex.sock");
+        let mut listener =
+            codex_uds::UnixListener::bind(&socket_path).await.expect("failed to bind test socket");
+        let server_service = Arc::clone(&service);
+        let server_task = tokio::spawn(async move {
+            let Ok(stream) = listener.accept().await else {
+                return;
+            };
+            let incoming =
+                tokio_stream::once(Ok::<_, std::io::Error>(crate::connection::Connection::Unix(stream)));
+            let _ = tonic::transport::Server::builder()
+                .add_service(crate::proto::codex_cli_server::CodexCliServer::from_arc(server_service))
+                .serve_with_incoming(incoming)
+                .await;
+        });
+
+        let connect_socket_path = socket_path.clone();
+        let channel = tonic::transport::Endpoint::try_from("http://codex-cli-grpc-bridge.invalid")
+            .expect("failed to build endpoint")
+            .connect_with_connector(tower::service_fn(move |_: tonic::transport::Uri| {
+                let socket_path = connect_socket_path.clone();
+                async move {
+                    codex_uds::UnixStream::connect(&socket_path).await.map(hyper_util::rt::TokioIo::new)
+                }
+            }))
+            .await
+            .expect("failed to connect to test socket");
+        let mut client = crate::proto::codex_cli_client::CodexCliClient::new(channel);
+
+        let pid_file = tempfile::NamedTempFile::new().expect("failed to create temp file");
+        let (input_tx, input_rx) = tokio::sync::mpsc::channel(4);
+        input_tx
+            .send(InteractiveCommandInput {
+                input: Some(Input::Start(RunCommandRequest {
+                    args: vec![
+                        "-c".to_string(),
+                        format!("echo $$ > {path}; sleep 60", path = pid_file.path().display()),
+                    ],
+                    ..Default::default()
+                })),
+            })
+            .await
+            .expect("failed to send start message");
+
+        let call_task = tokio::spawn(async move {
+            let _ = client.run_interactive_command(tokio_stream::wrappers::ReceiverStream::new(input_rx)).await;
+        });
+
+        // Give the child time to start and record its pid.
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        // Stand in for tonic dropping the in-flight handler future on a
+        // client disconnect by aborting the task serving the connection
+        // that future is running inside of.
+        server_task.abort();
+        let _ = server_task.await;
+        call_task.abort();
+        let _ = call_task.await;
+        // Allow the kernel a moment to finish tearing down the killed child.
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let child_pid = std::fs::read_to_string(pid_file.path())
+            .expect("child did not record its pid")
+            .trim()
+            .parse::<i32>()
+            .expect("child pid was not an integer");
+        let still_alive = unsafe { libc::kill(child_pid, 0) } == 0;
+        assert!(!still_alive, "child survived a dropped RunInteractiveCommand connection");
+    }
+
+    /// `run_interactive_command`'s `start` shares `RunCommandRequest`'s
+    /// shape, but several of its fields (like `nice`) have no way to be
+    /// honored by this streaming RPC. They must be rejected up front rather
+    /// than silently ignored. Drives the call over a real socket, like
+    /// `dropping_the_interactive_rpc_connection_kills_the_child`, since
+    /// `Streaming<InteractiveCommandInput>` can't be constructed in-process.
+    #[tokio::test]
+    async fn run_interactive_command_rejects_unsupported_nice_field() {
+        let service = Arc::new(CodexCliService::for_test("sh"));
+
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let socket_path = dir.path().join("codex.sock");
+        let mut listener =
+            codex_uds::UnixListener::bind(&socket_path).await.expect("failed to bind test socket");
+        let server_service = Arc::clone(&service);
+        let server_task = tokio::spawn(async move {
+            let Ok(stream) = listener.accept().await else {
+                return;
+            };
+            let incoming =
+                tokio_stream::once(Ok::<_, std::io::Error>(crate::connection::Connection::Unix(stream)));
+            let _ = tonic::transport::Server::builder()
+                .add_service(crate::proto::codex_cli_server::CodexCliServer::from_arc(server_service))
+                .serve_with_incoming(incoming)
+                .await;
+        });
+
+        let connect_socket_path = socket_path.clone();
+        let channel = tonic::transport::Endpoint::try_from("http://codex-cli-grpc-bridge.invalid")
+            .expect("failed to build endpoint")
+            .connect_with_connector(tower::service_fn(move |_: tonic::transport::Uri| {
+                let socket_path = connect_socket_path.clone();
+                async move {
+                    codex_uds::UnixStream::connect(&socket_path).await.map(hyper_util::rt::TokioIo::new)
+                }
+            }))
+            .await
+            .expect("failed to connect to test socket");
+        let mut client = crate::proto::codex_cli_client::CodexCliClient::new(channel);
+
+        let (input_tx, input_rx) = tokio::sync::mpsc::channel(4);
+        input_tx
+            .send(InteractiveCommandInput {
+                input: Some(Input::Start(RunCommandRequest {
+                    args: vec!["-c".to_string(), "true".to_string()],
+                    nice: Some(5),
+                    ..Default::default()
+                })),
+            })
+            .await
+            .expect("failed to send start message");
+
+        let status = client
+            .run_interactive_command(tokio_stream::wrappers::ReceiverStream::new(input_rx))
+            .await
+            .expect_err("nice on run_interactive_command's start should be rejected");
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        server_task.abort();
+        let _ = server_task.await;
+    }
+
+    /// Regression test for the interactive analog of
+    /// `run_command_does_not_deadlock_on_large_echoed_stdin`: `cat` echoes
+    /// every `StdinChunk` it reads, so sending several megabytes of chunks
+    /// before `CloseStdin` fills the stdout pipe while the server is still
+    /// forwarding stdin. Forwarding stdin sequentially before ever reading
+    /// stdout (as the stdin loop used to, prior to running concurrently with
+    /// the output readers) would leave `cat` blocked writing to a full pipe
+    /// and the server blocked writing to a child that has stopped reading —
+    /// a permanent deadlock that holds the child and the request's permits
+    /// forever. Drives the call over a real socket, like
+    /// `dropping_the_interactive_rpc_connection_kills_the_child`, since
+    /// `Streaming<InteractiveCommandInput>` can't be constructed in-process.
+    #[tokio::test]
+    async fn run_interactive_command_does_not_deadlock_on_large_echoed_stdin() {
+        let service = Arc::new(CodexCliService::for_test("cat").with_max_request_bytes(64 * 1024 * 1024));
+
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let socket_path = dir.path().join("codex.sock");
+        let mut listener =
+            codex_uds::UnixListener::bind(&socket_path).await.expect("failed to bind test socket");
+        let server_service = Arc::clone(&service);
+        let server_task = tokio::spawn(async move {
+            let Ok(stream) = listener.accept().await else {
+                return;
+            };
+            let incoming =
+                tokio_stream::once(Ok::<_, std::io::Error>(crate::connection::Connection::Unix(stream)));
+            let _ = tonic::transport::Server::builder()
+                .add_service(crate::proto::codex_cli_server::CodexCliServer::from_arc(server_service))
+                .serve_with_incoming(incoming)
+                .await;
+        });
+
+        let connect_socket_path = socket_path.clone();
+        let channel = tonic::transport::Endpoint::try_from("http://codex-cli-grpc-bridge.invalid")
+            .expect("failed to build endpoint")
+            .connect_with_connector(tower::service_fn(move |_: tonic::transport::Uri| {
+                let socket_path = connect_socket_path.clone();
+                async move {
+                    codex_uds::UnixStream::connect(&socket_path).await.map(hyper_util::rt::TokioIo::new)
+                }
+            }))
+            .await
+            .expect("failed to connect to test socket");
+        let mut client = crate::proto::codex_cli_client::CodexCliClient::new(channel);
+
+        let stdin = vec![b'x'; 8 * 1024 * 1024];
+        let (input_tx, input_rx) = tokio::sync::mpsc::channel(4);
+        let sender = input_tx.clone();
+        tokio::spawn(async move {
+            sender
+                .send(InteractiveCommandInput {
+                    input: Some(Input::Start(RunCommandRequest {
+                        args: Vec::new(),
+                        timeout_ms: Some(10_000),
+                        ..Default::default()
+                    })),
+                })
+                .await
+                .expect("failed to send start message");
+            for chunk in stdin.chunks(256 * 1024) {
+                sender
+                    .send(InteractiveCommandInput { input: Some(Input::StdinChunk(chunk.to_vec())) })
+                    .await
+                    .expect("failed to send stdin chunk");
+            }
+            sender
+                .send(InteractiveCommandInput { input: Some(Input::CloseStdin(true)) })
+                .await
+                .expect("failed to send close_stdin");
+        });
+
+        let response = tokio::time::timeout(
+            Duration::from_secs(10),
+            client.run_interactive_command(tokio_stream::wrappers::ReceiverStream::new(input_rx)),
+        )
+        .await
+        .expect("run_interactive_command deadlocked")
+        .expect("run_interactive_command failed")
+        .into_inner();
+        assert_eq!(response.exit_code, 0);
+        assert_eq!(response.stdout, vec![b'x'; 8 * 1024 * 1024]);
+
+        server_task.abort();
+        let _ = server_task.await;
+    }
+
+    /// Regression test for the same deadlock as
+    /// `run_interactive_command_does_not_deadlock_on_large_echoed_stdin`,
+    /// but via `start`'s initial `stdin` field rather than `stdin_chunk`s:
+    /// that field used to be written eagerly, before the output readers and
+    /// `forward_interactive_stdin` started running concurrently, so a large
+    /// enough payload there deadlocked the same way chunks did.
+    #[tokio::test]
+    async fn run_interactive_command_does_not_deadlock_on_large_initial_stdin() {
+        let service = Arc::new(CodexCliService::for_test("cat").with_max_request_bytes(64 * 1024 * 1024));
+
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let socket_path = dir.path().join("codex.sock");
+        let mut listener =
+            codex_uds::UnixListener::bind(&socket_path).await.expect("failed to bind test socket");
+        let server_service = Arc::clone(&service);
+        let server_task = tokio::spawn(async move {
+            let Ok(stream) = listener.accept().await else {
+                return;
+            };
+            let incoming =
+                tokio_stream::once(Ok::<_, std::io::Error>(crate::connection::Connection::Unix(stream)));
+            let _ = tonic::transport::Server::builder()
+                .add_service(crate::proto::codex_cli_server::CodexCliServer::from_arc(server_service))
+                .serve_with_incoming(incoming)
+                .await;
+        });
+
+        let connect_socket_path = socket_path.clone();
+        let channel = tonic::transport::Endpoint::try_from("http://codex-cli-grpc-bridge.invalid")
+            .expect("failed to build endpoint")
+            .connect_with_connector(tower::service_fn(move |_: tonic::transport::Uri| {
+                let socket_path = connect_socket_path.clone();
+                async move {
+                    codex_uds::UnixStream::connect(&socket_path).await.map(hyper_util::rt::TokioIo::new)
+                }
+            }))
+            .await
+            .expect("failed to connect to test socket");
+        let mut client = crate::proto::codex_cli_client::CodexCliClient::new(channel);
+
+        let stdin = vec![b'x'; 8 * 1024 * 1024];
+        let (input_tx, input_rx) = tokio::sync::mpsc::channel(4);
+        let sender = input_tx.clone();
+        tokio::spawn(async move {
+            sender
+                .send(InteractiveCommandInput {
+                    input: Some(Input::Start(RunCommandRequest {
+                        args: Vec::new(),
+                        timeout_ms: Some(10_000),
+                        stdin: stdin.clone(),
+                        ..Default::default()
+                    })),
+                })
+                .await
+                .expect("failed to send start message");
+            sender
+                .send(InteractiveCommandInput { input: Some(Input::CloseStdin(true)) })
+                .await
+                .expect("failed to send close_stdin");
+        });
+
+        let response = tokio::time::timeout(
+            Duration::from_secs(10),
+            client.run_interactive_command(tokio_stream::wrappers::ReceiverStream::new(input_rx)),
+        )
+        .await
+        .expect("run_interactive_command deadlocked")
+        .expect("run_interactive_command failed")
+        .into_inner();
+        assert_eq!(response.exit_code, 0);
+        assert_eq!(response.stdout, vec![b'x'; 8 * 1024 * 1024]);
+
+        server_task.abort();
+        let _ = server_task.await;
+    }
+
+    /// Regression test for a pipe-buffer deadlock: `cat` echoes stdin to
+    /// stdout, so a multi-megabyte payload fills both pipes at once. Writing
+    /// all of stdin before reading any output would hang forever once the
+    /// stdout pipe buffer filled up while `cat` was still blocked writing to
+    /// it; writing stdin concurrently with the output reads avoids that.
+    #[tokio::test]
+    async fn run_command_does_not_deadlock_on_large_echoed_stdin() {
+        let service = CodexCliService::for_test("cat").with_max_request_bytes(64 * 1024 * 1024);
+        let stdin = vec![b'x'; 8 * 1024 * 1024];
+        let request = Request::new(RunCommandRequest {
+            timeout_ms: Some(10_000),
+            stdin: stdin.clone(),
+            ..Default::default()
+        });
+
+        let response = service
+            .run_command(request)
+            .await
+            .expect("run_command deadlocked or failed")
+            .into_inner();
+        assert_eq!(response.exit_code, 0);
+        assert_eq!(response.stdout, stdin);
+    }
+
+    /// Empty `stdin` should close the child's stdin right away rather than
+    /// leaving it open, so a command that reads to EOF (like `cat` with no
+    /// input) exits promptly instead of hanging forever waiting for more
+    /// input that will never arrive.
+    #[tokio::test]
+    async fn run_command_with_empty_stdin_closes_it_immediately() {
+        let service = CodexCliService::for_test("cat");
+        let request = Request::new(RunCommandRequest {
+            timeout_ms: Some(5_000),
+            ..Default::default()
+        });
+
+        let response = tokio::time::timeout(Duration::from_secs(5), service.run_command(request))
+            .await
+            .expect("run_command hung waiting for stdin EOF")
+            .expect("run_command failed")
+            .into_inner();
+        assert_eq!(response.exit_code, 0);
+        assert!(response.stdout.is_empty());
+    }
+
+    /// A missing `--cli-path` is a misconfiguration, not a server bug, so it
+    /// should map to `FailedPrecondition` rather than the `Internal` status
+    /// a raw spawn error would otherwise surface as.
+    #[tokio::test]
+    async fn run_command_reports_missing_cli_path_as_failed_precondition() {
+        let service = CodexCliService::for_test("/nonexistent/codex-cli-that-does-not-exist");
+        let request = Request::new(RunCommandRequest::default());
+
+        let status = service
+            .run_command(request)
+            .await
+            .expect_err("expected a missing cli_path to fail");
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        assert!(status.message().contains("not found"), "unexpected message: {}", status.message());
+    }
+
+    /// A key with an embedded `=` is dropped rather than forwarded to the
+    /// child, since `Command::envs` would otherwise produce
+    /// platform-dependent behavior for it.
+    #[tokio::test]
+    async fn run_command_drops_env_keys_with_embedded_equals() {
+        let service = CodexCliService::for_test("sh");
+        let mut env = HashMap::new();
+        env.insert("GOOD_VAR".to_string(), "1".to_string());
+        env.insert("BAD=VAR".to_string(), "2".to_string());
+        let request = Request::new(RunCommandRequest {
+            args: vec!["-c".to_string(), "env".to_string()],
+            env,
+            ..Default::default()
+        });
+
+        let response = service
+            .run_command(request)
+            .await
+            .expect("run_command failed")
+            .into_inner();
+        let stdout = String::from_utf8_lossy(&response.stdout);
+        assert!(stdout.contains("GOOD_VAR=1"), "valid env var missing from child env: {stdout}");
+        assert!(!stdout.contains("BAD=VAR"), "malformed env key leaked into child env: {stdout}");
+    }
+
+    #[tokio::test]
+    async fn run_command_rejects_cwd_escaping_allowed_root_via_dotdot() {
+        let temp = tempfile::tempdir().expect("failed to create temp dir");
+        let allowed_root = temp.path().join("allowed");
+        let outside = temp.path().join("outside");
+        std::fs::create_dir(&allowed_root).expect("failed to create allowed root");
+        std::fs::create_dir(&outside).expect("failed to create outside dir");
+
+        let service = CodexCliService::for_test("sh").with_allowed_cwd_root(Some(allowed_root.clone()));
+        let escaping_cwd = allowed_root.join("..").join("outside");
+        let request = Request::new(RunCommandRequest {
+            args: vec!["-c".to_string(), "true".to_string()],
+            cwd: escaping_cwd.to_str().expect("path is not utf-8").to_string(),
+            ..Default::default()
+        });
+
+        let status = service
+            .run_command(request)
+            .await
+            .expect_err("cwd escaping --allowed-cwd-root via .. should be rejected");
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn run_command_rejects_stdout_file_path_escaping_allowed_dir_via_symlink() {
+        let temp = tempfile::tempdir().expect("failed to create temp dir");
+        let allowed_dir = temp.path().join("allowed");
+        let outside_dir = temp.path().join("outside");
+        std::fs::create_dir(&allowed_dir).expect("failed to create allowed dir");
+        std::fs::create_dir(&outside_dir).expect("failed to create outside dir");
+        std::os::unix::fs::symlink(&outside_dir, allowed_dir.join("escape"))
+            .expect("failed to create symlink");
+
+        let service = CodexCliService::for_test("sh").with_allowed_stdout_dir(Some(allowed_dir));
+        let escaping_path = temp.path().join("allowed").join("escape").join("out.txt");
+        let request = Request::new(RunCommandRequest {
+            args: vec!["-c".to_string(), "true".to_string()],
+            cwd: String::new(),
+            stdout_file_path: Some(escaping_path.to_str().expect("path is not utf-8").to_string()),
+            ..Default::default()
+        });
+
+        let status = service
+            .run_command(request)
+            .await
+            .expect_err("stdout_file_path escaping --allowed-stdout-dir via a symlink should be rejected");
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(
+            !outside_dir.join("out.txt").exists(),
+            "stdout file was created outside the allowed root"
+        );
+    }
+
+    #[tokio::test]
+    async fn run_command_rejects_stdin_file_path_escaping_allowed_dir_via_dotdot() {
+        let temp = tempfile::tempdir().expect("failed to create temp dir");
+        let allowed_dir = temp.path().join("allowed");
+        let outside_dir = temp.path().join("outside");
+        std::fs::create_dir(&allowed_dir).expect("failed to create allowed dir");
+        std::fs::create_dir(&outside_dir).expect("failed to create outside dir");
+        let secret_path = outside_dir.join("secret.txt");
+        std::fs::write(&secret_path, b"top secret").expect("failed to write secret file");
+
+        let service = CodexCliService::for_test("sh").with_allowed_stdin_dir(Some(allowed_dir.clone()));
+        let escaping_path = allowed_dir.join("..").join("outside").join("secret.txt");
+        let request = Request::new(RunCommandRequest {
+            args: vec!["-c".to_string(), "cat".to_string()],
+            cwd: String::new(),
+            stdin_file_path: Some(escaping_path.to_str().expect("path is not utf-8").to_string()),
+            ..Default::default()
+        });
+
+        let status = service
+            .run_command(request)
+            .await
+            .expect_err("stdin_file_path escaping --allowed-stdin-dir via .. should be rejected");
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn run_commands_returns_one_response_per_request_in_order() {
+        let service = CodexCliService::for_test("sh");
+        let request = Request::new(RunCommandsRequest {
+            requests: vec![
+                RunCommandRequest {
+                    args: vec!["-c".to_string(), "echo first".to_string()],
+                    ..Default::default()
+                },
+                RunCommandRequest {
+                    args: vec!["-c".to_string(), "echo second".to_string()],
+                    ..Default::default()
+                },
+            ],
+            stop_on_first_failure: false,
+        });
+
+        let response = service.run_commands(request).await.expect("run_commands failed").into_inner();
+
+        assert_eq!(response.responses.len(), 2);
+        assert!(response.responses[0].error.is_none());
+        assert!(response.responses[1].error.is_none());
+        assert!(String::from_utf8_lossy(&response.responses[0].stdout).contains("first"));
+        assert!(String::from_utf8_lossy(&response.responses[1].stdout).contains("second"));
+    }
+
+    /// An RPC-level failure (here, a `cwd` that fails validation) on one
+    /// request must not discard the responses already collected for the
+    /// rest of the batch, nor fail the whole `run_commands` call.
+    #[tokio::test]
+    async fn run_commands_reports_rpc_level_failure_inline_without_losing_other_responses() {
+        let temp = tempfile::tempdir().expect("failed to create temp dir");
+        let allowed_root = temp.path().join("allowed");
+        std::fs::create_dir(&allowed_root).expect("failed to create allowed root");
+
+        let service = CodexCliService::for_test("sh").with_allowed_cwd_root(Some(allowed_root.clone()));
+        let escaping_cwd = allowed_root.join("..").join("outside");
+        let request = Request::new(RunCommandsRequest {
+            requests: vec![
+                RunCommandRequest {
+                    args: vec!["-c".to_string(), "true".to_string()],
+                    cwd: allowed_root.to_str().expect("path is not utf-8").to_string(),
+                    ..Default::default()
+                },
+                RunCommandRequest {
+                    args: vec!["-c".to_string(), "true".to_string()],
+                    cwd: escaping_cwd.to_str().expect("path is not utf-8").to_string(),
+                    ..Default::default()
+                },
+            ],
+            stop_on_first_failure: false,
+        });
+
+        let response = service.run_commands(request).await.expect("run_commands failed").into_inner();
+
+        assert_eq!(response.responses.len(), 2, "one bad request should not discard the other's response");
+        assert!(response.responses[0].error.is_none());
+        let error = response.responses[1].error.as_ref().expect("escaping cwd should report an error");
+        assert_eq!(error.code, ErrorCode::InvalidArgument as i32);
+    }
+
+    /// Regression test: `stop_on_first_failure` must stop *dispatching new*
+    /// requests, but a request already in flight when the failure is
+    /// observed has to be allowed to finish rather than having its child
+    /// killed out from under it. With `--concurrency-limit 2` and three
+    /// requests, the first two are dispatched immediately; the second fails
+    /// right away while the first is still sleeping. The third must never
+    /// start (its pid file is never written), while the first must still
+    /// complete normally (its pid file is written and its response reports
+    /// success) instead of being torn down when the batch stops early.
+    #[tokio::test]
+    async fn run_commands_lets_in_flight_requests_finish_after_stop_on_first_failure() {
+        let slow_marker = tempfile::NamedTempFile::new().expect("failed to create temp file");
+        let never_marker = tempfile::NamedTempFile::new().expect("failed to create temp file");
+
+        let service = CodexCliService::for_test("sh").with_concurrency_limit(2);
+        let request = Request::new(RunCommandsRequest {
+            requests: vec![
+                RunCommandRequest {
+                    args: vec![
+                        "-c".to_string(),
+                        format!("sleep 0.5; echo done > {path}", path = slow_marker.path().display()),
+                    ],
+                    ..Default::default()
+                },
+                RunCommandRequest {
+                    args: vec!["-c".to_string(), "exit 1".to_string()],
+                    ..Default::default()
+                },
+                RunCommandRequest {
+                    args: vec!["-c".to_string(), format!("echo ran > {}", never_marker.path().display())],
+                    ..Default::default()
+                },
+            ],
+            stop_on_first_failure: true,
+        });
+
+        let response = service.run_commands(request).await.expect("run_commands failed").into_inner();
+
+        assert_eq!(response.responses.len(), 2, "the third request must never be dispatched");
+        assert_eq!(response.responses[0].exit_code, 0, "the in-flight first request must finish normally");
+        assert_eq!(
+            std::fs::read_to_string(slow_marker.path()).expect("failed to read marker file").trim(),
+            "done",
+            "the first request's child must not be killed when the batch stops early"
+        );
+        assert_eq!(response.responses[1].exit_code, 1);
+        assert!(
+            std::fs::read_to_string(never_marker.path()).expect("failed to read marker file").is_empty(),
+            "the third request must never have run"
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use rustls_pki_types::CertificateDer;
+use rustls_pki_types::PrivateKeyDer;
+use tokio_rustls::TlsAcceptor;
+
+/// Builds the [`TlsAcceptor`] used to terminate TLS on `--listen-addr`
+/// connections. The Unix socket never goes through this; it is accepted
+/// as plaintext regardless of these settings.
+///
+/// When `client_ca` is set, client certificates are required and verified
+/// against it, giving mutual TLS. Otherwise the connection is server-auth
+/// only, like a typical HTTPS endpoint.
+pub fn build_acceptor(
+    cert_path: &Path,
+    key_path: &Path,
+    client_ca_path: Option<&Path>,
+) -> anyhow::Result<TlsAcceptor> {
+    codex_utils_rustls_provider::ensure_rustls_crypto_provider();
+
+    let certs = load_certs(cert_path)?;
+    let key = load_private_key(key_path)?;
+
+    let builder = rustls::ServerConfig::builder();
+    let config = match client_ca_path {
+        Some(client_ca_path) => {
+            let mut roots = rustls::RootCertStore::empty();
+            for cert in load_certs(client_ca_path)? {
+                roots.add(cert)?;
+            }
+            let verifier =
+                rustls::server::WebPkiClientVerifier::builder(Arc::new(roots)).build()?;
+            builder
+                .with_client_cert_verifier(verifier)
+                .with_single_cert(certs, key)?
+        }
+        None => builder.with_no_client_auth().with_single_cert(certs, key)?,
+    };
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+fn load_certs(path: &Path) -> io::Result<Vec<CertificateDer<'static>>> {
+    let mut reader = io::BufReader::new(std::fs::File::open(path)?);
+    rustls_pemfile::certs(&mut reader).collect()
+}
+
+fn load_private_key(path: &Path) -> io::Result<PrivateKeyDer<'static>> {
+    let mut reader = io::BufReader::new(std::fs::File::open(path)?);
+    rustls_pemfile::private_key(&mut reader)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no private key found in PEM file"))
+}
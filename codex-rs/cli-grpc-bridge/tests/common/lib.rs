@@ -0,0 +1,145 @@
+//! An in-process [`CodexCliService`](codex_cli_grpc_bridge::CodexCliService)
+//! double for integration tests, so a consumer of [`CodexClient`] can
+//! exercise it against a scripted fake CLI instead of a real `codex` binary
+//! and a long-lived socket.
+//!
+//! The fake CLI is a `/bin/sh` script, so [`TestServer`] is Unix-only, same
+//! as the rest of this crate's `#[cfg(unix)]`-gated test coverage.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use clap::Parser;
+use codex_cli_grpc_bridge::Args;
+use codex_cli_grpc_bridge::CodexClient;
+use codex_cli_grpc_bridge::run_server;
+use tokio_util::sync::CancellationToken;
+
+/// Canned stdout/stderr/exit code the fake CLI a [`TestServer`] spawns
+/// always returns, regardless of the arguments it was invoked with.
+pub struct FakeCliOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub exit_code: i32,
+}
+
+impl FakeCliOutput {
+    /// A fake CLI that writes `stdout` and exits 0.
+    pub fn stdout(stdout: impl Into<String>) -> Self {
+        Self { stdout: stdout.into().into_bytes(), stderr: Vec::new(), exit_code: 0 }
+    }
+}
+
+/// The arguments and environment the fake CLI was invoked with, recorded by
+/// [`TestServer::last_invocation`].
+pub struct RecordedInvocation {
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+}
+
+/// An in-process `CodexCliService` bound to a temp Unix domain socket, with
+/// a fake CLI in place of a real `codex` binary. Dropping it cancels the
+/// server task; there's nothing else to clean up since the backing socket
+/// and fake CLI both live in a [`tempfile::TempDir`] that drops with it.
+pub struct TestServer {
+    dir: tempfile::TempDir,
+    shutdown: CancellationToken,
+    server_task: tokio::task::JoinHandle<()>,
+}
+
+impl TestServer {
+    /// Starts a server backed by a fake CLI that always returns `output`,
+    /// and returns it along with a client already connected to it.
+    pub async fn start(output: FakeCliOutput) -> anyhow::Result<(Self, CodexClient)> {
+        let dir = tempfile::tempdir()?;
+        let socket_path = dir.path().join("codex.sock");
+        let cli_path = write_fake_cli(dir.path(), &output)?;
+
+        let args = Args::parse_from([
+            "codex-cli-grpc-bridge",
+            "--socket-path",
+            &socket_path.to_string_lossy(),
+            "--cli-path",
+            &cli_path.to_string_lossy(),
+        ]);
+        let shutdown = CancellationToken::new();
+        let force_shutdown = CancellationToken::new();
+        let server_shutdown = shutdown.clone();
+        let server_task = tokio::spawn(async move {
+            if let Err(err) = run_server(args, server_shutdown, force_shutdown).await {
+                eprintln!("TestServer's run_server exited with an error: {err}");
+            }
+        });
+
+        // `connect_uds_pooled` dials lazily, so this succeeds even though
+        // the spawned task above hasn't necessarily bound the socket yet;
+        // the `ping` below is what actually waits for it, retrying via the
+        // same backoff a real client reconnecting after a restart would use.
+        let mut client = CodexClient::connect_uds_pooled(&socket_path, 20)?;
+        client.ping().await?;
+
+        Ok((Self { dir, shutdown, server_task }, client))
+    }
+
+    /// The args and env the fake CLI was last invoked with. `None` if it
+    /// hasn't been invoked yet. Only reflects the most recent invocation.
+    pub fn last_invocation(&self) -> anyhow::Result<Option<RecordedInvocation>> {
+        let args_path = self.dir.path().join("fake-cli.capture.args");
+        if !args_path.exists() {
+            return Ok(None);
+        }
+        let args = std::fs::read_to_string(args_path)?.lines().map(str::to_string).collect();
+        let env = std::fs::read_to_string(self.dir.path().join("fake-cli.capture.env"))?
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Ok(Some(RecordedInvocation { args, env }))
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+        self.server_task.abort();
+    }
+}
+
+/// Quotes `path` as a single POSIX shell word.
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
+
+/// Writes a `/bin/sh` script to `dir` that records its own args and env
+/// (for [`TestServer::last_invocation`]) and then replays `output`.
+fn write_fake_cli(dir: &Path, output: &FakeCliOutput) -> anyhow::Result<PathBuf> {
+    let stdout_path = dir.join("fake-cli.stdout");
+    let stderr_path = dir.join("fake-cli.stderr");
+    let args_capture_path = dir.join("fake-cli.capture.args");
+    let env_capture_path = dir.join("fake-cli.capture.env");
+    std::fs::write(&stdout_path, &output.stdout)?;
+    std::fs::write(&stderr_path, &output.stderr)?;
+
+    let script = format!(
+        "#!/bin/sh\n\
+         for arg in \"$@\"; do printf '%s\\n' \"$arg\"; done > {args}\n\
+         env > {env}\n\
+         cat {stdout}\n\
+         cat {stderr} >&2\n\
+         exit {exit_code}\n",
+        args = shell_quote(&args_capture_path),
+        env = shell_quote(&env_capture_path),
+        stdout = shell_quote(&stdout_path),
+        stderr = shell_quote(&stderr_path),
+        exit_code = output.exit_code,
+    );
+    let script_path = dir.join("fake-cli.sh");
+    std::fs::write(&script_path, script)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(script_path)
+}
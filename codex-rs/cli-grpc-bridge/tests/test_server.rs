@@ -0,0 +1,50 @@
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use cli_grpc_bridge_test_support::FakeCliOutput;
+use cli_grpc_bridge_test_support::TestServer;
+
+#[tokio::test]
+async fn run_command_passes_through_args_and_env() {
+    let (server, mut client) = TestServer::start(FakeCliOutput::stdout("hello\n"))
+        .await
+        .expect("failed to start TestServer");
+
+    let response = client
+        .run_command(
+            vec!["--flag".to_string(), "value".to_string()],
+            [("GREETING".to_string(), "hi".to_string())].into_iter().collect(),
+            std::env::temp_dir().to_string_lossy().into_owned(),
+            Vec::new(),
+        )
+        .await
+        .expect("run_command failed");
+
+    assert_eq!(response.stdout, b"hello\n");
+    assert_eq!(response.exit_code, 0);
+
+    let invocation = server
+        .last_invocation()
+        .expect("failed to read recorded invocation")
+        .expect("fake CLI was never invoked");
+    assert_eq!(invocation.args, vec!["--flag", "value"]);
+    assert_eq!(invocation.env.get("GREETING").map(String::as_str), Some("hi"));
+}
+
+#[tokio::test]
+async fn run_command_surfaces_a_nonzero_exit_code() {
+    let (_server, mut client) = TestServer::start(FakeCliOutput {
+        stdout: Vec::new(),
+        stderr: b"boom\n".to_vec(),
+        exit_code: 7,
+    })
+    .await
+    .expect("failed to start TestServer");
+
+    let response = client
+        .run_command(Vec::new(), std::collections::HashMap::new(), std::env::temp_dir().to_string_lossy().into_owned(), Vec::new())
+        .await
+        .expect("run_command failed");
+
+    assert_eq!(response.stderr, b"boom\n");
+    assert_eq!(response.exit_code, 7);
+}
@@ -32,6 +32,11 @@ pub struct UnixListener {
 
 impl UnixListener {
     /// Binds a new listener at `socket_path`.
+    ///
+    /// On Linux, a path starting with `@` binds an abstract-namespace socket
+    /// (e.g. `@codex.sock`) instead of a filesystem one; there's no socket
+    /// file to leave behind, so callers can skip any cleanup they'd
+    /// otherwise do for an ordinary path. Unsupported on other platforms.
     pub async fn bind(socket_path: impl AsRef<Path>) -> IoResult<Self> {
         platform::bind_listener(socket_path.as_ref())
             .await
@@ -42,6 +47,20 @@ impl UnixListener {
     pub async fn accept(&mut self) -> IoResult<UnixStream> {
         self.inner.accept().await.map(|inner| UnixStream { inner })
     }
+
+    /// Wraps an already-bound, already-listening socket fd, e.g. one handed
+    /// down by systemd socket activation. Unsupported on Windows, which has
+    /// no comparable fd-passing convention for `uds_windows` sockets.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be a valid, open file descriptor for a listening Unix
+    /// domain socket, and the caller must not use `fd` through any other
+    /// owner afterward.
+    #[cfg(unix)]
+    pub unsafe fn from_raw_fd(fd: std::os::fd::RawFd) -> IoResult<Self> {
+        unsafe { platform::listener_from_raw_fd(fd) }.map(|inner| Self { inner })
+    }
 }
 
 /// Async Unix domain socket stream.
@@ -50,12 +69,32 @@ pub struct UnixStream {
 }
 
 impl UnixStream {
-    /// Connects to `socket_path`.
+    /// Connects to `socket_path`. Accepts the same `@name` abstract-namespace
+    /// convention as [`UnixListener::bind`].
     pub async fn connect(socket_path: impl AsRef<Path>) -> IoResult<Self> {
         platform::connect_stream(socket_path.as_ref())
             .await
             .map(|inner| Self { inner })
     }
+
+    /// Reads the connecting peer's credentials via `SO_PEERCRED`. Linux-only;
+    /// other Unixes expose the equivalent (e.g. macOS's `LOCAL_PEERCRED`)
+    /// through a different struct layout, and Windows has no comparable
+    /// socket option at all.
+    #[cfg(target_os = "linux")]
+    pub fn peer_cred(&self) -> IoResult<PeerCredentials> {
+        platform::peer_cred(&self.inner)
+    }
+}
+
+/// Credentials of a Unix domain socket peer, as reported by the kernel at
+/// connection time via `SO_PEERCRED`. See [`UnixStream::peer_cred`].
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug)]
+pub struct PeerCredentials {
+    pub pid: i32,
+    pub uid: u32,
+    pub gid: u32,
 }
 
 impl AsyncRead for UnixStream {
@@ -138,9 +177,24 @@ mod platform {
     }
 
     pub(super) async fn bind_listener(socket_path: &Path) -> IoResult<Listener> {
+        if let Some(name) = abstract_socket_name(socket_path) {
+            return bind_abstract_listener(name).map(Listener);
+        }
         UnixListener::bind(socket_path).map(Listener)
     }
 
+    /// # Safety
+    ///
+    /// See [`super::UnixListener::from_raw_fd`].
+    pub(super) unsafe fn listener_from_raw_fd(fd: std::os::fd::RawFd) -> IoResult<Listener> {
+        use std::os::fd::FromRawFd;
+        use std::os::unix::net::UnixListener as StdUnixListener;
+
+        let std_listener = unsafe { StdUnixListener::from_raw_fd(fd) };
+        std_listener.set_nonblocking(true)?;
+        UnixListener::from_std(std_listener).map(Listener)
+    }
+
     impl Listener {
         pub(super) async fn accept(&mut self) -> IoResult<Stream> {
             self.0.accept().await.map(|(stream, _addr)| stream)
@@ -148,15 +202,107 @@ mod platform {
     }
 
     pub(super) async fn connect_stream(socket_path: &Path) -> IoResult<Stream> {
+        if let Some(name) = abstract_socket_name(socket_path) {
+            return connect_abstract_stream(name).await;
+        }
         UnixStream::connect(socket_path).await
     }
 
+    /// Returns the name encoded in `socket_path` when it uses the
+    /// conventional `@name` prefix for a Linux abstract-namespace socket
+    /// (mirroring systemd and other tools that can't put a literal NUL byte
+    /// in a command-line argument). Abstract sockets are a Linux-only kernel
+    /// feature, so this always returns `None` on other Unixes, leaving such
+    /// a path to be bound/connected as an ordinary (almost certainly
+    /// nonexistent) filesystem path.
+    #[cfg(target_os = "linux")]
+    fn abstract_socket_name(socket_path: &Path) -> Option<&std::ffi::OsStr> {
+        use std::os::unix::ffi::OsStrExt;
+        let name = socket_path.as_os_str().as_bytes().strip_prefix(b"@")?;
+        Some(std::ffi::OsStr::from_bytes(name))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn abstract_socket_name(_socket_path: &Path) -> Option<&std::ffi::OsStr> {
+        None
+    }
+
+    #[cfg(target_os = "linux")]
+    fn bind_abstract_listener(name: &std::ffi::OsStr) -> IoResult<UnixListener> {
+        use std::os::linux::net::SocketAddrExt;
+        use std::os::unix::ffi::OsStrExt;
+        use std::os::unix::net::SocketAddr;
+        use std::os::unix::net::UnixListener as StdUnixListener;
+
+        let addr = SocketAddr::from_abstract_name(name.as_bytes())?;
+        let std_listener = StdUnixListener::bind_addr(&addr)?;
+        std_listener.set_nonblocking(true)?;
+        UnixListener::from_std(std_listener)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn bind_abstract_listener(_name: &std::ffi::OsStr) -> IoResult<UnixListener> {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "abstract-namespace Unix sockets are only supported on Linux",
+        ))
+    }
+
+    #[cfg(target_os = "linux")]
+    async fn connect_abstract_stream(name: &std::ffi::OsStr) -> IoResult<UnixStream> {
+        use std::os::linux::net::SocketAddrExt;
+        use std::os::unix::ffi::OsStrExt;
+        use std::os::unix::net::SocketAddr;
+        use std::os::unix::net::UnixStream as StdUnixStream;
+
+        let addr = SocketAddr::from_abstract_name(name.as_bytes())?;
+        let std_stream = StdUnixStream::connect_addr(&addr)?;
+        std_stream.set_nonblocking(true)?;
+        UnixStream::from_std(std_stream)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    async fn connect_abstract_stream(_name: &std::ffi::OsStr) -> IoResult<UnixStream> {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "abstract-namespace Unix sockets are only supported on Linux",
+        ))
+    }
+
     pub(super) async fn is_stale_socket_path(socket_path: &Path) -> IoResult<bool> {
         Ok(fs::symlink_metadata(socket_path)
             .await?
             .file_type()
             .is_socket())
     }
+
+    #[cfg(target_os = "linux")]
+    pub(super) fn peer_cred(stream: &Stream) -> IoResult<super::PeerCredentials> {
+        use std::os::fd::AsRawFd;
+
+        let mut ucred: libc::ucred = unsafe { std::mem::zeroed() };
+        let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+        // SAFETY: `ucred` and `len` are valid, correctly-sized out-parameters
+        // for `getsockopt`, and `stream`'s fd stays open for the duration of
+        // this call since we only borrow it.
+        let result = unsafe {
+            libc::getsockopt(
+                stream.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                &mut ucred as *mut _ as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(super::PeerCredentials {
+            pid: ucred.pid,
+            uid: ucred.uid,
+            gid: ucred.gid,
+        })
+    }
 }
 
 #[cfg(windows)]